@@ -1,8 +1,9 @@
 use types::*;
 use rustc_serialize::json;
-use hyper::HttpResult;
-use hyper::method::Method::{Get, Post, Head, Delete};
+use hyper::method::Method::{Get, Post, Put, Head, Delete};
 use connection::Connection;
+use error::EsResult;
+use transport::RequestOptions;
 
 new_query_struct!{ IndexRequest(index: String, typ: String, id: Option<String>,
                                 source: json::Object) {
@@ -18,6 +19,7 @@ new_query_struct!{ IndexRequest(index: String, typ: String, id: Option<String>,
         (consistency: Consistency, None),
         (op_type: OpType, Some(OpType::Create)),
         (parent: Parent, None),
+        (pipeline: Pipeline, None),
         (refresh: Refresh, None),
         (routing: Routing, None),
         (timeout: Timeout, None),
@@ -27,13 +29,16 @@ new_query_struct!{ IndexRequest(index: String, typ: String, id: Option<String>,
         (version_type: VersionType, None)
     ],
     body => source,
-    method => Post
+    // with an id the document is replaced, which is safe to retry
+    fn_method => |self| {
+        if self.id.is_some() { Put } else { Post }
+    }
 }}
 
 new_query_struct!{ UpdateRequest(index: String, typ: String, id: String,
                                 update_body: json::Object) {
     fn_path => |self| {
-        vec![self.index.to_string(), self.typ.to_string(), self.id.to_string()]
+        vec![self.index.to_string(), self.typ.to_string(), self.id.to_string(), "_update".to_string()]
     },
     query_params => [
         (consistency: Consistency, None),
@@ -110,6 +115,8 @@ new_query_struct!{ ExistsRequest(index: String, typ: String, id: String) {
     method => Head
 }}
 
+impl_exists!{ ExistsRequest }
+
 new_query_struct!{ DeleteRequest(index: String, typ: String, id: String) {
     fn_path => |self| {
         vec![self.index.to_string(), self.typ.to_string(), self.id.to_string()]
@@ -134,11 +141,13 @@ pub struct BulkRequest<'a> {
     consistency: Option<Consistency>,
     index: Option<Index>,
     typ: Option<Type>,
+    pipeline: Option<Pipeline>,
     refresh: Option<Refresh>,
     routing: Option<Routing>,
     timeout: Option<Timeout>,
     version: Option<Version>,
-    version_type: Option<VersionType>
+    version_type: Option<VersionType>,
+    request_options: RequestOptions
 }
 
 impl<'a> BulkRequest<'a> {
@@ -149,11 +158,13 @@ impl<'a> BulkRequest<'a> {
             consistency: None,
             index: None,
             typ: None,
+            pipeline: None,
             refresh: None,
             routing: None,
             timeout: None,
             version: None,
-            version_type: None
+            version_type: None,
+            request_options: RequestOptions::new()
         }
     }
 
@@ -162,19 +173,23 @@ impl<'a> BulkRequest<'a> {
     field_setter!{ BulkRequest , (consistency, Consistency) }
     field_setter!{ BulkRequest , (index, Index) }
     field_setter!{ BulkRequest , (typ, Type) }
+    field_setter!{ BulkRequest , (pipeline, Pipeline) }
     field_setter!{ BulkRequest , (refresh, Refresh) }
     field_setter!{ BulkRequest , (routing, Routing) }
     field_setter!{ BulkRequest , (timeout, Timeout) }
     field_setter!{ BulkRequest , (version, Version) }
     field_setter!{ BulkRequest , (version_type, VersionType) }
 
+    request_option_setters!{}
+
     pub fn get_path(&self) -> Vec<String>  { vec!["_bulk".to_string()] }
 
-    pub fn execute(&self) -> HttpResult<String> {
+    pub fn execute(&self) -> EsResult<String> {
         let params: Vec<(&str, String)> = param_pairs! {
             self.consistency,
             self.index,
             self.typ,
+            self.pipeline,
             self.refresh,
             self.routing,
             self.timeout,
@@ -182,6 +197,6 @@ impl<'a> BulkRequest<'a> {
             self.version_type
         };
         let bod: String = self.payload.to_string();
-        self.connection.request(Post, self.get_path(), params, Some(bod.as_bytes()))
+        self.connection.request(Post, self.get_path(), params, Some(bod.as_bytes()), &self.request_options)
     }
 }
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use rustc_serialize::json::{self, Json, ToJson};
+use error::{Error, EsResult};
+use response::FromResponse;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alias {
+    pub index: String,
+    pub alias: String,
+    pub filter: Option<Json>,
+    pub routing: Option<String>,
+    pub index_routing: Option<String>,
+    pub search_routing: Option<String>,
+    pub is_write_index: Option<bool>
+}
+
+impl Alias {
+    pub fn new(index: &str, alias: &str) -> Alias {
+        Alias {
+            index: index.to_string(),
+            alias: alias.to_string(),
+            filter: None,
+            routing: None,
+            index_routing: None,
+            search_routing: None,
+            is_write_index: None
+        }
+    }
+
+    pub fn filter(mut self, filter: Json) -> Alias {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn routing(mut self, routing: &str) -> Alias {
+        self.routing = Some(routing.to_string());
+        self
+    }
+
+    pub fn index_routing(mut self, routing: &str) -> Alias {
+        self.index_routing = Some(routing.to_string());
+        self
+    }
+
+    pub fn search_routing(mut self, routing: &str) -> Alias {
+        self.search_routing = Some(routing.to_string());
+        self
+    }
+
+    pub fn is_write_index(mut self, is_write_index: bool) -> Alias {
+        self.is_write_index = Some(is_write_index);
+        self
+    }
+
+    // the alias definition without `index`/`alias`, as sent to
+    // `PUT /{index}/_alias/{name}`
+    pub fn body(&self) -> json::Object {
+        let mut obj: json::Object = BTreeMap::new();
+        if let Some(ref f) = self.filter { obj.insert("filter".to_string(), f.clone()); }
+        if let Some(ref r) = self.routing { obj.insert("routing".to_string(), r.to_json()); }
+        if let Some(ref r) = self.index_routing { obj.insert("index_routing".to_string(), r.to_json()); }
+        if let Some(ref r) = self.search_routing { obj.insert("search_routing".to_string(), r.to_json()); }
+        if let Some(w) = self.is_write_index { obj.insert("is_write_index".to_string(), w.to_json()); }
+        obj
+    }
+}
+
+impl ToJson for Alias {
+    fn to_json(&self) -> Json {
+        let mut obj = self.body();
+        obj.insert("index".to_string(), self.index.to_json());
+        obj.insert("alias".to_string(), self.alias.to_json());
+        Json::Object(obj)
+    }
+}
+
+// A single entry of a `POST /_aliases` request. All actions of one request
+// are applied atomically.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AliasAction {
+    Add(Alias),
+    Remove(String, String),
+    RemoveIndex(String)
+}
+
+impl ToJson for AliasAction {
+    fn to_json(&self) -> Json {
+        let (name, body) = match *self {
+            AliasAction::Add(ref alias) => ("add", alias.to_json()),
+            AliasAction::Remove(ref index, ref alias) => {
+                let mut obj: json::Object = BTreeMap::new();
+                obj.insert("index".to_string(), index.to_json());
+                obj.insert("alias".to_string(), alias.to_json());
+                ("remove", Json::Object(obj))
+            },
+            AliasAction::RemoveIndex(ref index) => {
+                let mut obj: json::Object = BTreeMap::new();
+                obj.insert("index".to_string(), index.to_json());
+                ("remove_index", Json::Object(obj))
+            }
+        };
+
+        let mut outer: json::Object = BTreeMap::new();
+        outer.insert(name.to_string(), body);
+        Json::Object(outer)
+    }
+}
+
+pub fn actions_body(actions: &[AliasAction]) -> json::Object {
+    let mut obj: json::Object = BTreeMap::new();
+    obj.insert("actions".to_string(), Json::Array(actions.iter().map(|a| a.to_json()).collect()));
+    obj
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AliasMetadata {
+    pub filter: Option<Json>,
+    pub index_routing: Option<String>,
+    pub search_routing: Option<String>,
+    pub is_write_index: Option<bool>
+}
+
+impl AliasMetadata {
+    pub fn new() -> AliasMetadata {
+        AliasMetadata { filter: None, index_routing: None, search_routing: None, is_write_index: None }
+    }
+
+    pub fn from_json(json: &Json) -> AliasMetadata {
+        AliasMetadata {
+            filter: json.find("filter").map(|f| f.clone()),
+            index_routing: json.find("index_routing").and_then(|r| r.as_string()).map(|r| r.to_string()),
+            search_routing: json.find("search_routing").and_then(|r| r.as_string()).map(|r| r.to_string()),
+            is_write_index: json.find("is_write_index").and_then(|w| w.as_boolean())
+        }
+    }
+}
+
+impl ToJson for AliasMetadata {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = BTreeMap::new();
+        if let Some(ref f) = self.filter { obj.insert("filter".to_string(), f.clone()); }
+        if let Some(ref r) = self.index_routing { obj.insert("index_routing".to_string(), r.to_json()); }
+        if let Some(ref r) = self.search_routing { obj.insert("search_routing".to_string(), r.to_json()); }
+        if let Some(w) = self.is_write_index { obj.insert("is_write_index".to_string(), w.to_json()); }
+        Json::Object(obj)
+    }
+}
+
+// `GET /_alias` responses, keyed by index and then by alias name
+impl FromResponse for BTreeMap<String, BTreeMap<String, AliasMetadata>> {
+    fn from_json(json: Json) -> EsResult<BTreeMap<String, BTreeMap<String, AliasMetadata>>> {
+        let indices = match json {
+            Json::Object(obj) => obj,
+            other => return Err(Error::UnexpectedResponse(other.to_string()))
+        };
+
+        let mut out = BTreeMap::new();
+        for (index, value) in indices.into_iter() {
+            let mut aliases = BTreeMap::new();
+            if let Some(obj) = value.find("aliases").and_then(|a| a.as_object()) {
+                for (name, meta) in obj.iter() {
+                    aliases.insert(name.to_string(), AliasMetadata::from_json(meta));
+                }
+            }
+            out.insert(index, aliases);
+        }
+        Ok(out)
+    }
+}
+
+#[test]
+fn test_alias_actions_body() {
+    let body = actions_body(&[
+        AliasAction::Remove("logs-1".to_string(), "logs".to_string()),
+        AliasAction::Add(Alias::new("logs-2", "logs").is_write_index(true))
+    ]);
+    let expected = Json::from_str(r#"{"actions": [
+        {"remove": {"index": "logs-1", "alias": "logs"}},
+        {"add": {"index": "logs-2", "alias": "logs", "is_write_index": true}}
+    ]}"#).unwrap();
+    assert_eq!(Json::Object(body), expected);
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use rustc_serialize::json::{self, Json, ToJson};
+use error::{Error, EsResult};
+use response::FromResponse;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analyze {
+    pub text: Vec<String>,
+    pub analyzer: Option<String>,
+    pub tokenizer: Option<String>,
+    pub filter: Vec<String>,
+    pub char_filter: Vec<String>,
+    pub field: Option<String>,
+    pub explain: Option<bool>
+}
+
+impl Analyze {
+    pub fn text(text: &str) -> Analyze {
+        Analyze::texts(vec![text.to_string()])
+    }
+
+    pub fn texts(text: Vec<String>) -> Analyze {
+        Analyze {
+            text: text,
+            analyzer: None,
+            tokenizer: None,
+            filter: Vec::new(),
+            char_filter: Vec::new(),
+            field: None,
+            explain: None
+        }
+    }
+
+    pub fn analyzer(mut self, analyzer: &str) -> Analyze {
+        self.analyzer = Some(analyzer.to_string());
+        self
+    }
+
+    pub fn tokenizer(mut self, tokenizer: &str) -> Analyze {
+        self.tokenizer = Some(tokenizer.to_string());
+        self
+    }
+
+    pub fn filter(mut self, filter: &str) -> Analyze {
+        self.filter.push(filter.to_string());
+        self
+    }
+
+    pub fn char_filter(mut self, char_filter: &str) -> Analyze {
+        self.char_filter.push(char_filter.to_string());
+        self
+    }
+
+    // analyze with the analyzer configured for `field` in the index mapping
+    pub fn field(mut self, field: &str) -> Analyze {
+        self.field = Some(field.to_string());
+        self
+    }
+
+    pub fn explain(mut self, explain: bool) -> Analyze {
+        self.explain = Some(explain);
+        self
+    }
+
+    pub fn body(&self) -> json::Object {
+        let mut obj: json::Object = BTreeMap::new();
+        if self.text.len() == 1 {
+            obj.insert("text".to_string(), self.text[0].to_json());
+        } else {
+            obj.insert("text".to_string(), self.text.to_json());
+        }
+        if let Some(ref a) = self.analyzer { obj.insert("analyzer".to_string(), a.to_json()); }
+        if let Some(ref t) = self.tokenizer { obj.insert("tokenizer".to_string(), t.to_json()); }
+        if !self.filter.is_empty() { obj.insert("filter".to_string(), self.filter.to_json()); }
+        if !self.char_filter.is_empty() { obj.insert("char_filter".to_string(), self.char_filter.to_json()); }
+        if let Some(ref f) = self.field { obj.insert("field".to_string(), f.to_json()); }
+        if let Some(e) = self.explain { obj.insert("explain".to_string(), e.to_json()); }
+        obj
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyzeToken {
+    pub token: String,
+    pub start_offset: u64,
+    pub end_offset: u64,
+    pub token_type: String,
+    pub position: u64
+}
+
+impl AnalyzeToken {
+    fn from_json(json: &Json) -> EsResult<AnalyzeToken> {
+        let missing = |key: &str| Error::UnexpectedResponse(format!("token is missing `{}`: {}", key, json));
+        Ok(AnalyzeToken {
+            token: try!(json.find("token").and_then(|t| t.as_string()).ok_or(missing("token"))).to_string(),
+            start_offset: try!(json.find("start_offset").and_then(|o| o.as_u64()).ok_or(missing("start_offset"))),
+            end_offset: try!(json.find("end_offset").and_then(|o| o.as_u64()).ok_or(missing("end_offset"))),
+            token_type: try!(json.find("type").and_then(|t| t.as_string()).ok_or(missing("type"))).to_string(),
+            position: try!(json.find("position").and_then(|p| p.as_u64()).ok_or(missing("position")))
+        })
+    }
+}
+
+// `detail` is only returned, untyped, when `explain` was requested
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyzeResponse {
+    pub tokens: Vec<AnalyzeToken>,
+    pub detail: Option<Json>
+}
+
+impl FromResponse for AnalyzeResponse {
+    fn from_json(json: Json) -> EsResult<AnalyzeResponse> {
+        let mut tokens = Vec::new();
+        if let Some(list) = json.find("tokens").and_then(|t| t.as_array()) {
+            for t in list.iter() {
+                tokens.push(try!(AnalyzeToken::from_json(t)));
+            }
+        }
+        Ok(AnalyzeResponse { tokens: tokens, detail: json.find("detail").map(|d| d.clone()) })
+    }
+}
+
+#[test]
+fn test_analyze_response() {
+    let body = r#"{"tokens": [
+        {"token": "quick", "start_offset": 4, "end_offset": 9, "type": "<ALPHANUM>", "position": 1}
+    ]}"#;
+    let resp = AnalyzeResponse::from_response(body).unwrap();
+    assert_eq!(resp.tokens, vec![AnalyzeToken {
+        token: "quick".to_string(),
+        start_offset: 4,
+        end_offset: 9,
+        token_type: "<ALPHANUM>".to_string(),
+        position: 1
+    }]);
+    assert_eq!(resp.detail, None);
+}
@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use rustc_serialize::json::{self, Json};
+use hyper::method::Method::Get;
+use connection::Connection;
+use error::{Error, EsResult};
+use response::FromResponse;
+use types::*;
+
+//
+// _cat requests ask for `format=json` by default. Rows are decoded from the
+// JSON array, or from the plain-text table (with a `v` header line) when the
+// format is overridden.
+//
+
+macro_rules! cat_request {
+    ($c:ident => $row:ty, [ $($segment:expr),* ]) => {
+        new_query_struct!{ $c(target: Option<StringList>) {
+            fn_path => |self| {
+                let mut path: Vec<String> = vec!["_cat".to_string() $(, $segment.to_string())*];
+                if let Some(ref target) = self.target { path.push(target.to_string()); }
+                path
+            },
+            query_params => [
+                (format: Format, Some(Format("json".to_string()))),
+                (columns: Columns, None),
+                (sort: SortBy, None),
+                (bytes: ByteUnit, None),
+                (show_headers: ShowHeaders, None),
+                (local: Local, None),
+                (master_timeout: MasterTimeout, None)
+            ],
+            method => Get
+        }}
+
+        impl_send!{ $c => CatRows<$row> }
+    }
+}
+
+cat_request!{ IndicesRequest => CatIndex, ["indices"] }
+cat_request!{ ShardsRequest => CatShard, ["shards"] }
+cat_request!{ NodesRequest => CatNode, ["nodes"] }
+cat_request!{ HealthRequest => CatHealth, ["health"] }
+cat_request!{ AliasesRequest => CatAlias, ["aliases"] }
+cat_request!{ AllocationRequest => CatAllocation, ["allocation"] }
+cat_request!{ CountRequest => CatCount, ["count"] }
+cat_request!{ ThreadPoolRequest => CatThreadPool, ["thread_pool"] }
+cat_request!{ SegmentsRequest => CatSegment, ["segments"] }
+cat_request!{ RecoveryRequest => CatRecovery, ["recovery"] }
+cat_request!{ PendingTasksRequest => CatPendingTask, ["pending_tasks"] }
+
+pub type Row = BTreeMap<String, String>;
+
+pub trait CatRow: Sized {
+    fn from_row(row: &Row) -> EsResult<Self>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatRows<T>(pub Vec<T>);
+
+impl<T: CatRow> FromResponse for CatRows<T> {
+    fn from_json(json: Json) -> EsResult<CatRows<T>> {
+        let rows = try!(json_rows(json));
+        let mut out = Vec::new();
+        for row in rows.iter() {
+            out.push(try!(T::from_row(row)));
+        }
+        Ok(CatRows(out))
+    }
+
+    fn from_response(body: &str) -> EsResult<CatRows<T>> {
+        match Json::from_str(body) {
+            Ok(json) => {
+                if json.find("error").is_some() && json.find("status").is_some() {
+                    return Err(Error::Elasticsearch(json));
+                }
+                CatRows::from_json(json)
+            },
+            Err(_) => {
+                let mut out = Vec::new();
+                for row in parse_text_table(body).iter() {
+                    out.push(try!(T::from_row(row)));
+                }
+                Ok(CatRows(out))
+            }
+        }
+    }
+}
+
+fn json_rows(json: Json) -> EsResult<Vec<Row>> {
+    let array = match json {
+        Json::Array(a) => a,
+        other => return Err(Error::UnexpectedResponse(other.to_string()))
+    };
+
+    let mut rows = Vec::new();
+    for entry in array.into_iter() {
+        let obj = match entry {
+            Json::Object(obj) => obj,
+            other => return Err(Error::UnexpectedResponse(other.to_string()))
+        };
+        let mut row = BTreeMap::new();
+        for (key, value) in obj.into_iter() {
+            // unavailable values, e.g. the node of an unassigned shard, are null
+            match value {
+                Json::Null => {},
+                Json::String(s) => { row.insert(key, s); },
+                other => { row.insert(key, other.to_string()); }
+            }
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+// Parses a plain-text `_cat` table whose first line holds the column names.
+// Lines with as many whitespace-separated values as there are columns are
+// split on whitespace; otherwise, since empty cells leave gaps, values are
+// cut at the offsets where each column name starts. Offsets count
+// characters, not bytes, as the cluster pads columns by character.
+pub fn parse_text_table(text: &str) -> Vec<Row> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header = match lines.next() {
+        Some(h) => h,
+        None => return Vec::new()
+    };
+
+    let mut columns: Vec<(usize, String)> = Vec::new();
+    let mut in_word = false;
+    for (i, c) in header.chars().enumerate() {
+        if c.is_whitespace() {
+            in_word = false;
+        } else if !in_word {
+            in_word = true;
+            columns.push((i, String::new()));
+        }
+        if in_word { columns.last_mut().unwrap().1.push(c); }
+    }
+
+    let mut rows = Vec::new();
+    for line in lines {
+        let mut row = BTreeMap::new();
+        let values: Vec<&str> = line.split_whitespace().collect();
+        if values.len() == columns.len() {
+            for (&(_, ref name), value) in columns.iter().zip(values.iter()) {
+                row.insert(name.to_string(), value.to_string());
+            }
+        } else {
+            let chars: Vec<char> = line.chars().collect();
+            for (i, &(start, ref name)) in columns.iter().enumerate() {
+                let end = if i + 1 < columns.len() { columns[i + 1].0 } else { chars.len() };
+                if start >= chars.len() { break; }
+                let value: String = chars[start..::std::cmp::min(end, chars.len())].iter().cloned().collect();
+                let value = value.trim();
+                if !value.is_empty() { row.insert(name.to_string(), value.to_string()); }
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+fn column<T: FromStr>(row: &Row, name: &str) -> EsResult<Option<T>> {
+    match row.get(name) {
+        None => Ok(None),
+        Some(value) => match T::from_str(value) {
+            Ok(v) => Ok(Some(v)),
+            Err(_) => Err(Error::UnexpectedResponse(format!("invalid value for column `{}`: {}", name, value)))
+        }
+    }
+}
+
+// Fields are `None` when the column was not selected with `h` or has no value.
+macro_rules! cat_row {
+    ($c:ident { $($field:ident : $t:ty => $column:expr),* }) => {
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct $c {
+            $( pub $field: Option<$t>,)*
+        }
+
+        impl CatRow for $c {
+            fn from_row(row: &Row) -> EsResult<$c> {
+                Ok($c {
+                    $( $field: try!(column(row, $column)),)*
+                })
+            }
+        }
+    }
+}
+
+cat_row!{ CatIndex {
+    health: String => "health",
+    status: String => "status",
+    index: String => "index",
+    uuid: String => "uuid",
+    pri: u64 => "pri",
+    rep: u64 => "rep",
+    docs_count: u64 => "docs.count",
+    docs_deleted: u64 => "docs.deleted",
+    store_size: String => "store.size",
+    pri_store_size: String => "pri.store.size"
+}}
+
+cat_row!{ CatShard {
+    index: String => "index",
+    shard: u64 => "shard",
+    prirep: String => "prirep",
+    state: String => "state",
+    docs: u64 => "docs",
+    store: String => "store",
+    ip: String => "ip",
+    node: String => "node"
+}}
+
+cat_row!{ CatNode {
+    ip: String => "ip",
+    heap_percent: u64 => "heap.percent",
+    ram_percent: u64 => "ram.percent",
+    cpu: u64 => "cpu",
+    load_1m: f64 => "load_1m",
+    node_role: String => "node.role",
+    master: String => "master",
+    name: String => "name"
+}}
+
+cat_row!{ CatHealth {
+    epoch: u64 => "epoch",
+    timestamp: String => "timestamp",
+    cluster: String => "cluster",
+    status: String => "status",
+    node_total: u64 => "node.total",
+    node_data: u64 => "node.data",
+    shards: u64 => "shards",
+    pri: u64 => "pri",
+    relo: u64 => "relo",
+    init: u64 => "init",
+    unassign: u64 => "unassign",
+    pending_tasks: u64 => "pending_tasks",
+    active_shards_percent: String => "active_shards_percent"
+}}
+
+cat_row!{ CatAlias {
+    alias: String => "alias",
+    index: String => "index",
+    filter: String => "filter",
+    routing_index: String => "routing.index",
+    routing_search: String => "routing.search",
+    is_write_index: String => "is_write_index"
+}}
+
+cat_row!{ CatAllocation {
+    shards: u64 => "shards",
+    disk_indices: String => "disk.indices",
+    disk_used: String => "disk.used",
+    disk_avail: String => "disk.avail",
+    disk_total: String => "disk.total",
+    disk_percent: u64 => "disk.percent",
+    host: String => "host",
+    ip: String => "ip",
+    node: String => "node"
+}}
+
+cat_row!{ CatCount {
+    epoch: u64 => "epoch",
+    timestamp: String => "timestamp",
+    count: u64 => "count"
+}}
+
+cat_row!{ CatThreadPool {
+    node_name: String => "node_name",
+    name: String => "name",
+    active: u64 => "active",
+    queue: u64 => "queue",
+    rejected: u64 => "rejected"
+}}
+
+cat_row!{ CatSegment {
+    index: String => "index",
+    shard: u64 => "shard",
+    prirep: String => "prirep",
+    ip: String => "ip",
+    segment: String => "segment",
+    generation: u64 => "generation",
+    docs_count: u64 => "docs.count",
+    docs_deleted: u64 => "docs.deleted",
+    size: String => "size",
+    size_memory: String => "size.memory",
+    committed: bool => "committed",
+    searchable: bool => "searchable",
+    version: String => "version",
+    compound: bool => "compound"
+}}
+
+cat_row!{ CatRecovery {
+    index: String => "index",
+    shard: u64 => "shard",
+    time: String => "time",
+    recovery_type: String => "type",
+    stage: String => "stage",
+    source_node: String => "source_node",
+    target_node: String => "target_node",
+    files_percent: String => "files_percent",
+    bytes_recovered: String => "bytes_recovered",
+    bytes_total: String => "bytes_total",
+    bytes_percent: String => "bytes_percent",
+    translog_ops_percent: String => "translog_ops_percent"
+}}
+
+cat_row!{ CatPendingTask {
+    insert_order: u64 => "insertOrder",
+    time_in_queue: String => "timeInQueue",
+    priority: String => "priority",
+    source: String => "source"
+}}
+
+#[test]
+fn test_parse_text_table() {
+    let text = "health status index   pri rep docs.count\n\
+                green  open   logs-1    5   1       1200\n\
+                red    open   logs-2    5   1           \n";
+    let rows = parse_text_table(text);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get("docs.count"), Some(&"1200".to_string()));
+
+    let index = CatIndex::from_row(&rows[1]).unwrap();
+    assert_eq!(index.health, Some("red".to_string()));
+    assert_eq!(index.index, Some("logs-2".to_string()));
+    assert_eq!(index.rep, Some(1));
+    assert_eq!(index.docs_count, None);
+}
+
+#[test]
+fn test_parse_text_table_non_ascii() {
+    // the empty `docs.count` cell forces offset-based parsing, and the byte
+    // offset of that column falls inside the `ñ` of the index name
+    let text = "health index           docs.count node\n\
+                green  ñandúñandú-ñ               nœud-1\n";
+    let rows = parse_text_table(text);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get("index"), Some(&"ñandúñandú-ñ".to_string()));
+    assert_eq!(rows[0].get("docs.count"), None);
+    assert_eq!(rows[0].get("node"), Some(&"nœud-1".to_string()));
+}
@@ -1,8 +1,31 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
 use rustc_serialize::json;
+use rustc_serialize::json::{Json, ToJson};
 use url::Url;
 use connection::Connection;
-use actions::{CountRequest, GetRequest, IndexRequest, ExistsRequest, DeleteRequest};
+use transport::{Transport, Timeouts};
+use actions::{CountRequest, GetRequest, IndexRequest, ExistsRequest, DeleteRequest, UpdateRequest};
 use indices;
+use cluster;
+use nodes;
+use cat;
+use snapshot::{self, Repository, SnapshotConfig, RestoreConfig};
+use ingest::{self, IngestPipeline};
+use aliases::{self, Alias, AliasAction};
+use auth::Credentials;
+use analyze::Analyze;
+use cluster::ClusterSettings;
+use config::IndexConfig;
+use error::{Error, EsResult};
+use mapping::Mapping;
+use response::{AcknowledgedResponse, ShardsResponse};
+use settings::IndexSettings;
+use templates::IndexTemplate;
+use retry::RetryPolicy;
+use tls::TlsConfig;
+use rollover::RolloverConditions;
+use scripts::{self, ScriptRef, StoredScript};
 use types::*;
 
 //
@@ -12,7 +35,12 @@ use types::*;
 #[derive(Debug, Clone, PartialEq)]
 pub struct Client {
     pub connection: Connection,
-    pub indices: IndicesClient
+    pub indices: IndicesClient,
+    pub cluster: ClusterClient,
+    pub nodes: NodesClient,
+    pub cat: CatClient,
+    pub snapshot: SnapshotClient,
+    pub ingest: IngestClient
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -28,24 +56,416 @@ impl IndicesClient {
     pub fn exists(&self, indices: StringList) -> indices::ExistsRequest {
         indices::ExistsRequest::new(&self.connection, indices)
     }
+
+    pub fn analyze(&self, index: Option<String>, analyze: &Analyze) -> indices::AnalyzeRequest {
+        indices::AnalyzeRequest::new(&self.connection, index, analyze.body())
+    }
+
+    pub fn create(&self, index: &str, config: &IndexConfig) -> indices::CreateRequest {
+        indices::CreateRequest::new(&self.connection, index.to_string(), config.to_object())
+    }
+
+    pub fn put_mapping(&self, indices: StringList, typ: &str, mapping: &Mapping) -> indices::PutMappingRequest {
+        indices::PutMappingRequest::new(&self.connection, indices, typ.to_string(), mapping.to_json())
+    }
+
+    pub fn get_mapping(&self, indices: Option<StringList>, typ: Option<String>) -> indices::GetMappingRequest {
+        indices::GetMappingRequest::new(&self.connection, indices, typ)
+    }
+
+    pub fn get_settings(&self, indices: Option<StringList>, name: Option<StringList>) -> indices::GetSettingsRequest {
+        indices::GetSettingsRequest::new(&self.connection, indices, name)
+    }
+
+    pub fn update_settings(&self, indices: StringList, settings: &IndexSettings) -> indices::UpdateSettingsRequest {
+        indices::UpdateSettingsRequest::new(&self.connection, indices, settings.to_json())
+    }
+
+    pub fn refresh(&self, indices: StringList) -> indices::RefreshRequest {
+        indices::RefreshRequest::new(&self.connection, indices)
+    }
+
+    pub fn forcemerge(&self, indices: StringList) -> indices::ForceMergeRequest {
+        indices::ForceMergeRequest::new(&self.connection, indices)
+    }
+
+    pub fn flush(&self, indices: StringList) -> indices::FlushRequest {
+        indices::FlushRequest::new(&self.connection, indices)
+    }
+
+    pub fn clear_cache(&self, indices: StringList) -> indices::ClearCacheRequest {
+        indices::ClearCacheRequest::new(&self.connection, indices)
+    }
+
+    pub fn stats(&self, indices: StringList, metric: Option<StringList>) -> indices::StatsRequest {
+        indices::StatsRequest::new(&self.connection, indices, metric)
+    }
+
+    pub fn segments(&self, indices: StringList) -> indices::SegmentsRequest {
+        indices::SegmentsRequest::new(&self.connection, indices)
+    }
+
+    pub fn recovery(&self, indices: StringList) -> indices::RecoveryRequest {
+        indices::RecoveryRequest::new(&self.connection, indices)
+    }
+
+    pub fn get_alias(&self, indices: Option<StringList>, name: Option<StringList>) -> indices::GetAliasRequest {
+        indices::GetAliasRequest::new(&self.connection, indices, name)
+    }
+
+    pub fn put_alias(&self, alias: &Alias) -> indices::PutAliasRequest {
+        indices::PutAliasRequest::new(&self.connection, string_list![alias.index], alias.alias.to_string(), alias.body())
+    }
+
+    pub fn delete_alias(&self, indices: StringList, name: StringList) -> indices::DeleteAliasRequest {
+        indices::DeleteAliasRequest::new(&self.connection, indices, name)
+    }
+
+    pub fn update_aliases(&self, actions: &[AliasAction]) -> indices::UpdateAliasesRequest {
+        indices::UpdateAliasesRequest::new(&self.connection, aliases::actions_body(actions))
+    }
+
+    // Atomically moves `alias` from index `from` to index `to`.
+    pub fn swap_alias(&self, alias: &str, from: &str, to: &str) -> indices::UpdateAliasesRequest {
+        self.update_aliases(&[
+            AliasAction::Remove(from.to_string(), alias.to_string()),
+            AliasAction::Add(Alias::new(to, alias))
+        ])
+    }
+
+    pub fn put_template(&self, name: &str, template: &IndexTemplate) -> indices::PutTemplateRequest {
+        indices::PutTemplateRequest::new(&self.connection, name.to_string(), template.to_object())
+    }
+
+    pub fn get_template(&self, name: Option<StringList>) -> indices::GetTemplateRequest {
+        indices::GetTemplateRequest::new(&self.connection, name)
+    }
+
+    pub fn delete_template(&self, name: &str) -> indices::DeleteTemplateRequest {
+        indices::DeleteTemplateRequest::new(&self.connection, name.to_string())
+    }
+
+    pub fn exists_template(&self, name: StringList) -> indices::ExistsTemplateRequest {
+        indices::ExistsTemplateRequest::new(&self.connection, name)
+    }
+
+    pub fn rollover(&self, alias: &str, new_index: Option<String>, conditions: &RolloverConditions,
+                    config: &IndexConfig) -> indices::RolloverRequest {
+        indices::RolloverRequest::new(&self.connection, alias.to_string(), new_index, conditions.body(config))
+    }
+
+    pub fn shrink(&self, index: &str, target: &str, config: &IndexConfig) -> indices::ShrinkRequest {
+        indices::ShrinkRequest::new(&self.connection, index.to_string(), target.to_string(), config.to_object())
+    }
+
+    pub fn split(&self, index: &str, target: &str, config: &IndexConfig) -> indices::SplitRequest {
+        indices::SplitRequest::new(&self.connection, index.to_string(), target.to_string(), config.to_object())
+    }
+
+    // Runs `ingest` with refreshes disabled and replicas dropped to zero on
+    // `index`, then puts the original settings back, refreshes, and
+    // force-merges down to `max_num_segments` if given. Settings are restored
+    // whether or not `ingest` succeeds; an error from `ingest` takes
+    // precedence over one raised while restoring.
+    pub fn bulk_load<T, F>(&self, index: &str, max_num_segments: Option<u64>, ingest: F) -> EsResult<T>
+        where F: FnOnce() -> EsResult<T> {
+        let indices = string_list![index];
+
+        let mut current = try!(self.get_settings(Some(indices.clone()), None).flat_settings(true).send());
+        let original = match current.remove(index) {
+            Some(entry) => entry.settings,
+            None => return Err(Error::UnexpectedResponse(format!("no settings returned for index {}", index)))
+        };
+
+        let acknowledged = |response: AcknowledgedResponse| if response.acknowledged { Ok(()) } else {
+            Err(Error::UnexpectedResponse(format!("settings update on index {} was not acknowledged", index)))
+        };
+        let all_shards = |response: ShardsResponse| if response.shards.failed == 0 { Ok(()) } else {
+            Err(Error::UnexpectedResponse(format!("{} of {} shards of index {} failed: {:?}", response.shards.failed,
+                                                  response.shards.total, index, response.shards.failures)))
+        };
+
+        let bulk_settings = IndexSettings::new().refresh_interval("-1").number_of_replicas(0);
+        try!(self.update_settings(indices.clone(), &bulk_settings).send().and_then(&acknowledged));
+
+        let result = ingest();
+
+        // settings that were not explicitly set are reset to their defaults
+        let restore = IndexSettings::new()
+            .set("index.refresh_interval", original.refresh_interval.map_or(Json::Null, |r| r.to_json()))
+            .set("index.number_of_replicas", original.number_of_replicas.map_or(Json::Null, |n| n.to_json()));
+
+        let restored = self.update_settings(indices.clone(), &restore).send().and_then(&acknowledged)
+            .and_then(|_| self.refresh(indices.clone()).send().and_then(&all_shards))
+            .and_then(|_| match max_num_segments {
+                Some(n) => self.forcemerge(indices.clone()).max_num_segments(n).send().and_then(&all_shards),
+                None => Ok(())
+            });
+
+        match (result, restored) {
+            (Err(e), _) => Err(e),
+            (Ok(_), Err(e)) => Err(e),
+            (Ok(t), Ok(_)) => Ok(t)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterClient {
+    pub connection: Connection
+}
+
+impl ClusterClient {
+    pub fn new(conn: Connection) -> ClusterClient {
+        ClusterClient { connection: conn }
+    }
+
+    pub fn health(&self, indices: StringList) -> cluster::HealthRequest {
+        cluster::HealthRequest::new(&self.connection, indices)
+    }
+
+    pub fn state(&self, metrics: Option<StringList>, indices: Option<StringList>) -> cluster::StateRequest {
+        cluster::StateRequest::new(&self.connection, metrics, indices)
+    }
+
+    pub fn stats(&self, node_id: Option<StringList>) -> cluster::StatsRequest {
+        cluster::StatsRequest::new(&self.connection, node_id)
+    }
+
+    pub fn get_settings(&self) -> cluster::GetSettingsRequest {
+        cluster::GetSettingsRequest::new(&self.connection)
+    }
+
+    pub fn put_settings(&self, settings: &ClusterSettings) -> cluster::PutSettingsRequest {
+        cluster::PutSettingsRequest::new(&self.connection, settings.to_json())
+    }
+
+    pub fn pending_tasks(&self) -> cluster::PendingTasksRequest {
+        cluster::PendingTasksRequest::new(&self.connection)
+    }
+
+    // `commands` are reroute commands such as `{"move": {...}}`
+    pub fn reroute(&self, commands: Vec<Json>) -> cluster::RerouteRequest {
+        let mut body: json::Object = BTreeMap::new();
+        body.insert("commands".to_string(), Json::Array(commands));
+        cluster::RerouteRequest::new(&self.connection, body)
+    }
+
+    pub fn allocation_explain(&self, explain_body: json::Object) -> cluster::AllocationExplainRequest {
+        cluster::AllocationExplainRequest::new(&self.connection, explain_body)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodesClient {
+    pub connection: Connection
+}
+
+impl NodesClient {
+    pub fn new(conn: Connection) -> NodesClient {
+        NodesClient { connection: conn }
+    }
+
+    pub fn info(&self, node_id: Option<StringList>, metric: Option<StringList>) -> nodes::InfoRequest {
+        nodes::InfoRequest::new(&self.connection, node_id, metric)
+    }
+
+    pub fn stats(&self, node_id: Option<StringList>, metric: Option<StringList>) -> nodes::StatsRequest {
+        nodes::StatsRequest::new(&self.connection, node_id, metric)
+    }
+
+    pub fn hot_threads(&self, node_id: Option<StringList>) -> nodes::HotThreadsRequest {
+        nodes::HotThreadsRequest::new(&self.connection, node_id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatClient {
+    pub connection: Connection
+}
+
+impl CatClient {
+    pub fn new(conn: Connection) -> CatClient {
+        CatClient { connection: conn }
+    }
+
+    pub fn indices(&self, target: Option<StringList>) -> cat::IndicesRequest {
+        cat::IndicesRequest::new(&self.connection, target)
+    }
+
+    pub fn shards(&self, target: Option<StringList>) -> cat::ShardsRequest {
+        cat::ShardsRequest::new(&self.connection, target)
+    }
+
+    pub fn nodes(&self) -> cat::NodesRequest {
+        cat::NodesRequest::new(&self.connection, None)
+    }
+
+    pub fn health(&self) -> cat::HealthRequest {
+        cat::HealthRequest::new(&self.connection, None)
+    }
+
+    pub fn aliases(&self, target: Option<StringList>) -> cat::AliasesRequest {
+        cat::AliasesRequest::new(&self.connection, target)
+    }
+
+    pub fn allocation(&self, target: Option<StringList>) -> cat::AllocationRequest {
+        cat::AllocationRequest::new(&self.connection, target)
+    }
+
+    pub fn count(&self, target: Option<StringList>) -> cat::CountRequest {
+        cat::CountRequest::new(&self.connection, target)
+    }
+
+    pub fn thread_pool(&self, target: Option<StringList>) -> cat::ThreadPoolRequest {
+        cat::ThreadPoolRequest::new(&self.connection, target)
+    }
+
+    pub fn segments(&self, target: Option<StringList>) -> cat::SegmentsRequest {
+        cat::SegmentsRequest::new(&self.connection, target)
+    }
+
+    pub fn recovery(&self, target: Option<StringList>) -> cat::RecoveryRequest {
+        cat::RecoveryRequest::new(&self.connection, target)
+    }
+
+    pub fn pending_tasks(&self) -> cat::PendingTasksRequest {
+        cat::PendingTasksRequest::new(&self.connection, None)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotClient {
+    pub connection: Connection
+}
+
+impl SnapshotClient {
+    pub fn new(conn: Connection) -> SnapshotClient {
+        SnapshotClient { connection: conn }
+    }
+
+    pub fn put_repository(&self, repository: &str, definition: &Repository) -> snapshot::PutRepositoryRequest {
+        snapshot::PutRepositoryRequest::new(&self.connection, repository.to_string(), definition.to_json())
+    }
+
+    pub fn get_repository(&self, repository: Option<StringList>) -> snapshot::GetRepositoryRequest {
+        snapshot::GetRepositoryRequest::new(&self.connection, repository)
+    }
+
+    pub fn delete_repository(&self, repository: StringList) -> snapshot::DeleteRepositoryRequest {
+        snapshot::DeleteRepositoryRequest::new(&self.connection, repository)
+    }
+
+    pub fn verify_repository(&self, repository: &str) -> snapshot::VerifyRepositoryRequest {
+        snapshot::VerifyRepositoryRequest::new(&self.connection, repository.to_string())
+    }
+
+    pub fn create(&self, repository: &str, snapshot: &str, config: &SnapshotConfig) -> snapshot::CreateSnapshotRequest {
+        snapshot::CreateSnapshotRequest::new(&self.connection, repository.to_string(), snapshot.to_string(), config.body())
+    }
+
+    pub fn get(&self, repository: &str, snapshot: StringList) -> snapshot::GetSnapshotRequest {
+        snapshot::GetSnapshotRequest::new(&self.connection, repository.to_string(), snapshot)
+    }
+
+    pub fn status(&self, repository: Option<String>, snapshot: Option<StringList>) -> snapshot::SnapshotStatusRequest {
+        snapshot::SnapshotStatusRequest::new(&self.connection, repository, snapshot)
+    }
+
+    pub fn delete(&self, repository: &str, snapshot: &str) -> snapshot::DeleteSnapshotRequest {
+        snapshot::DeleteSnapshotRequest::new(&self.connection, repository.to_string(), snapshot.to_string())
+    }
+
+    pub fn restore(&self, repository: &str, snapshot: &str, config: &RestoreConfig) -> snapshot::RestoreSnapshotRequest {
+        snapshot::RestoreSnapshotRequest::new(&self.connection, repository.to_string(), snapshot.to_string(), config.body())
+    }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngestClient {
+    pub connection: Connection
+}
+
+impl IngestClient {
+    pub fn new(conn: Connection) -> IngestClient {
+        IngestClient { connection: conn }
+    }
+
+    pub fn put_pipeline(&self, id: &str, pipeline: &IngestPipeline) -> ingest::PutPipelineRequest {
+        ingest::PutPipelineRequest::new(&self.connection, id.to_string(), pipeline.to_json())
+    }
+
+    pub fn get_pipeline(&self, id: Option<StringList>) -> ingest::GetPipelineRequest {
+        ingest::GetPipelineRequest::new(&self.connection, id)
+    }
+
+    pub fn delete_pipeline(&self, id: &str) -> ingest::DeletePipelineRequest {
+        ingest::DeletePipelineRequest::new(&self.connection, id.to_string())
+    }
+
+    pub fn simulate(&self, id: Option<String>, pipeline: Option<&IngestPipeline>,
+                    docs: Vec<json::Object>) -> ingest::SimulatePipelineRequest {
+        ingest::SimulatePipelineRequest::new(&self.connection, id, ingest::simulate_body(pipeline, docs))
+    }
+}
 
 impl Client {
 
     pub fn new_with_url_host(host: Url) -> Client {
-        let conn = Connection::new(host);
-        Client {
-            connection: conn.clone(),
-            indices: IndicesClient::new(conn.clone())
-        }
+        Client::new_with_connection(Connection::new(host))
     }
 
     pub fn new_with_str_host(host: &str) -> Client {
-        let conn =Connection::new(Url::parse(host).unwrap());
+        Client::new_with_url_host(Url::parse(host).unwrap())
+    }
+
+    // Sends every request through `transport` instead of the default
+    // hyper-backed one.
+    pub fn new_with_transport(host: Url, transport: Arc<Transport>) -> Client {
+        Client::new_with_connection(Connection::with_transport(host, transport))
+    }
+
+    // Sends `credentials` with every request, replacing any given in the
+    // host url.
+    pub fn with_credentials(self, credentials: Credentials) -> Client {
+        Client::new_with_connection(self.connection.with_credentials(credentials))
+    }
+
+    // Client-wide connect and read timeouts; requests can override them with
+    // `connect_timeout` and `read_timeout`.
+    pub fn with_timeouts(self, timeouts: Timeouts) -> Client {
+        Client::new_with_connection(self.connection.with_timeouts(timeouts))
+    }
+
+    // Retries failed requests as `policy` allows; by default nothing is
+    // retried.
+    pub fn with_retry_policy(self, policy: RetryPolicy) -> Client {
+        Client::new_with_connection(self.connection.with_retry_policy(policy))
+    }
+
+    // Connects to https hosts with `tls`, keeping the transport's other
+    // settings. Fails if its certificates or keys can't be loaded, or if the
+    // transport can't take TLS settings.
+    pub fn with_tls(self, tls: TlsConfig) -> EsResult<Client> {
+        let transport = try!(self.connection.transport().with_tls_config(tls));
+        Ok(Client::new_with_connection(self.connection.replace_transport(transport)))
+    }
+
+    // Sends a header with every request; requests can override it with
+    // `header`, or tag themselves with `opaque_id`.
+    pub fn with_header(self, name: &str, value: &str) -> Client {
+        Client::new_with_connection(self.connection.with_header(name, value))
+    }
+
+    fn new_with_connection(conn: Connection) -> Client {
         Client {
             connection: conn.clone(),
-            indices: IndicesClient::new(conn.clone())
+            indices: IndicesClient::new(conn.clone()),
+            cluster: ClusterClient::new(conn.clone()),
+            nodes: NodesClient::new(conn.clone()),
+            cat: CatClient::new(conn.clone()),
+            snapshot: SnapshotClient::new(conn.clone()),
+            ingest: IngestClient::new(conn.clone())
         }
     }
 
@@ -68,6 +488,40 @@ impl Client {
     pub fn delete(&self, index: &str, typ: &str, id: &str) -> DeleteRequest {
         DeleteRequest::new(&self.connection, index.to_string(), typ.to_string(), id.to_string())
     }
+
+    pub fn update(&self, index: &str, typ: &str, id: &str, update_body: json::Object) -> UpdateRequest {
+        UpdateRequest::new(&self.connection, index.to_string(), typ.to_string(), id.to_string(), update_body)
+    }
+
+    pub fn update_with_script(&self, index: &str, typ: &str, id: &str, script: &ScriptRef) -> UpdateRequest {
+        self.update(index, typ, id, script.update_body())
+    }
+
+    pub fn put_script(&self, id: &str, script: &StoredScript) -> scripts::PutScriptRequest {
+        scripts::PutScriptRequest::new(&self.connection, id.to_string(), script.body())
+    }
+
+    pub fn get_script(&self, id: &str) -> scripts::GetScriptRequest {
+        scripts::GetScriptRequest::new(&self.connection, id.to_string())
+    }
+
+    pub fn delete_script(&self, id: &str) -> scripts::DeleteScriptRequest {
+        scripts::DeleteScriptRequest::new(&self.connection, id.to_string())
+    }
+
+    pub fn search_template(&self, indices: Option<StringList>, template: &ScriptRef) -> scripts::SearchTemplateRequest {
+        scripts::SearchTemplateRequest::new(&self.connection, indices, template.template_body())
+    }
+
+    // each search is a header (`index`, `preference`, ...) and a template
+    pub fn msearch_template(&self, indices: Option<StringList>,
+                            searches: Vec<(json::Object, ScriptRef)>) -> scripts::MsearchTemplateRequest {
+        scripts::MsearchTemplateRequest::new(&self.connection, indices, searches)
+    }
+
+    pub fn render_template(&self, template: &ScriptRef) -> scripts::RenderTemplateRequest {
+        scripts::RenderTemplateRequest::new(&self.connection, None, template.template_body())
+    }
 }
 
 // #[test]
@@ -90,3 +544,51 @@ impl Client {
 
 //     println!("{:?}", resp);
 // }
+
+#[test]
+fn test_bulk_load_restores_settings() {
+    use hyper::method::Method::{Get, Put, Post};
+    use mock::{MockTransport, Expectation};
+
+    let settings = r#"{"logs": {"settings": {"index.refresh_interval": "5s", "index.number_of_replicas": "1"}}}"#;
+    let bulk = r#"{"index.refresh_interval": "-1", "index.number_of_replicas": 0}"#;
+    let restore = r#"{"index.refresh_interval": "5s", "index.number_of_replicas": 1}"#;
+    let shards = r#"{"_shards": {"total": 2, "successful": 2, "failed": 0}}"#;
+
+    let mock = MockTransport::new();
+    mock.expect(Expectation::new(Get, "/logs/_settings").respond(200, settings));
+    mock.expect(Expectation::new(Put, "/logs/_settings").body_str(bulk).respond(200, r#"{"acknowledged": true}"#));
+    mock.expect(Expectation::new(Put, "/logs/_settings").body_str(restore).respond(200, r#"{"acknowledged": true}"#));
+    mock.expect(Expectation::new(Post, "/logs/_refresh").respond(200, shards));
+
+    // a restore the cluster refuses is reported
+    mock.expect(Expectation::new(Get, "/logs/_settings").respond(200, settings));
+    mock.expect(Expectation::new(Put, "/logs/_settings").respond(200, r#"{"acknowledged": true}"#));
+    mock.expect(Expectation::new(Put, "/logs/_settings").respond(200, r#"{"acknowledged": false}"#));
+
+    // settings are restored when the closure fails, and its error returned
+    mock.expect(Expectation::new(Get, "/logs/_settings").respond(200, settings));
+    mock.expect(Expectation::new(Put, "/logs/_settings").body_str(bulk).respond(200, r#"{"acknowledged": true}"#));
+    mock.expect(Expectation::new(Put, "/logs/_settings").body_str(restore).respond(200, r#"{"acknowledged": true}"#));
+    mock.expect(Expectation::new(Post, "/logs/_refresh").respond(200, shards));
+
+    // the index is force-merged after the refresh
+    mock.expect(Expectation::new(Get, "/logs/_settings").respond(200, settings));
+    mock.expect(Expectation::new(Put, "/logs/_settings").body_str(bulk).respond(200, r#"{"acknowledged": true}"#));
+    mock.expect(Expectation::new(Put, "/logs/_settings").body_str(restore).respond(200, r#"{"acknowledged": true}"#));
+    mock.expect(Expectation::new(Post, "/logs/_refresh").respond(200, shards));
+    mock.expect(Expectation::new(Post, "/logs/_forcemerge").query("max_num_segments", "1").respond(200, shards));
+
+    let client = mock.client();
+    assert_eq!(client.indices.bulk_load("logs", None, || Ok(42)).unwrap(), 42);
+    assert!(client.indices.bulk_load("logs", None, || Ok(42)).is_err());
+
+    let failed: EsResult<u32> = Err(Error::UnexpectedResponse("ingest failed".to_string()));
+    match client.indices.bulk_load("logs", None, || failed) {
+        Err(Error::UnexpectedResponse(ref message)) => assert_eq!(message, "ingest failed"),
+        other => panic!("expected the closure's error, got {:?}", other)
+    }
+
+    assert_eq!(client.indices.bulk_load("logs", Some(1), || Ok(42)).unwrap(), 42);
+    mock.verify();
+}
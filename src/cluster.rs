@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+use rustc_serialize::Decodable;
+use rustc_serialize::json::{self, Json, ToJson};
+use hyper::method::Method::{Get, Put, Post};
+use connection::Connection;
+use error::{Error, EsResult};
+use response::{FromResponse, decode_json, find_field, find_string};
+use settings::flatten;
+use stats::{DocsStats, StoreStats};
+use types::*;
+
+new_query_struct!{ HealthRequest(indices: StringList) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_cluster".to_string(), "health".to_string()];
+        if !self.indices.0.is_empty() { path.push(self.indices.to_string()); }
+        path
+    },
+    query_params => [
+        (level: Level, None),
+        (local: Local, None),
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None),
+        (wait_for_active_shards: WaitForActiveShards, None),
+        (wait_for_nodes: WaitForNodes, None),
+        (wait_for_no_relocating_shards: WaitForNoRelocatingShards, None),
+        (wait_for_status: WaitForStatus, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ HealthRequest => ClusterHealth }
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct ShardHealth {
+    pub status: HealthStatus,
+    pub primary_active: bool,
+    pub active_shards: u64,
+    pub relocating_shards: u64,
+    pub initializing_shards: u64,
+    pub unassigned_shards: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct IndexHealth {
+    pub status: HealthStatus,
+    pub number_of_shards: u64,
+    pub number_of_replicas: u64,
+    pub active_primary_shards: u64,
+    pub active_shards: u64,
+    pub relocating_shards: u64,
+    pub initializing_shards: u64,
+    pub unassigned_shards: u64,
+    pub shards: Option<BTreeMap<String, ShardHealth>>
+}
+
+// `timed_out` is set when a `wait_for_*` condition was not met within
+// `timeout`; the remaining fields then describe the cluster as it was.
+// `indices` is only present for `level=indices` or `level=shards`.
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct ClusterHealth {
+    pub cluster_name: String,
+    pub status: HealthStatus,
+    pub timed_out: bool,
+    pub number_of_nodes: u64,
+    pub number_of_data_nodes: u64,
+    pub active_primary_shards: u64,
+    pub active_shards: u64,
+    pub relocating_shards: u64,
+    pub initializing_shards: u64,
+    pub unassigned_shards: u64,
+    pub delayed_unassigned_shards: Option<u64>,
+    pub number_of_pending_tasks: Option<u64>,
+    pub number_of_in_flight_fetch: Option<u64>,
+    pub task_max_waiting_in_queue_millis: Option<u64>,
+    pub active_shards_percent_as_number: Option<f64>,
+    pub indices: Option<BTreeMap<String, IndexHealth>>
+}
+
+decodable_response!(ClusterHealth);
+
+new_query_struct!{ StateRequest(metrics: Option<StringList>, indices: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_cluster".to_string(), "state".to_string()];
+        match (&self.metrics, &self.indices) {
+            (&Some(ref metrics), _) => path.push(metrics.to_string()),
+            (&None, &Some(_)) => path.push("_all".to_string()),
+            (&None, &None) => {}
+        }
+        if let Some(ref indices) = self.indices { path.push(indices.to_string()); }
+        path
+    },
+    query_params => [
+        (local: Local, None),
+        (master_timeout: MasterTimeout, None),
+        (flat_settings: FlatSettings, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ StateRequest => ClusterState }
+
+new_query_struct!{ StatsRequest(node_id: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_cluster".to_string(), "stats".to_string()];
+        if let Some(ref node_id) = self.node_id {
+            path.push("nodes".to_string());
+            path.push(node_id.to_string());
+        }
+        path
+    },
+    query_params => [
+        (flat_settings: FlatSettings, None),
+        (timeout: Timeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ StatsRequest => ClusterStats }
+
+new_query_struct!{ GetSettingsRequest() {
+    fn_path => |self| {
+        vec!["_cluster".to_string(), "settings".to_string()]
+    },
+    query_params => [
+        (flat_settings: FlatSettings, None),
+        (include_defaults: IncludeDefaults, None),
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ GetSettingsRequest => ClusterSettings }
+
+new_query_struct!{ PutSettingsRequest(settings: json::Json) {
+    fn_path => |self| {
+        vec!["_cluster".to_string(), "settings".to_string()]
+    },
+    query_params => [
+        (flat_settings: FlatSettings, None),
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    body => settings,
+    method => Put
+}}
+
+impl_send!{ PutSettingsRequest => ClusterSettings }
+
+new_query_struct!{ PendingTasksRequest() {
+    fn_path => |self| {
+        vec!["_cluster".to_string(), "pending_tasks".to_string()]
+    },
+    query_params => [
+        (local: Local, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ PendingTasksRequest => PendingTasks }
+
+new_query_struct!{ RerouteRequest(commands: json::Object) {
+    fn_path => |self| {
+        vec!["_cluster".to_string(), "reroute".to_string()]
+    },
+    query_params => [
+        (dry_run: DryRun, None),
+        (explain: Explain, None),
+        (retry_failed: RetryFailed, None),
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    body => commands,
+    method => Post
+}}
+
+impl_send!{ RerouteRequest => Json }
+
+// An empty body explains the first unassigned shard found.
+new_query_struct!{ AllocationExplainRequest(explain_body: json::Object) {
+    fn_path => |self| {
+        vec!["_cluster".to_string(), "allocation".to_string(), "explain".to_string()]
+    },
+    query_params => [
+        (include_yes_decisions: IncludeYesDecisions, None),
+        (include_disk_info: IncludeDiskInfo, None)
+    ],
+    body => explain_body,
+    method => Post
+}}
+
+impl_send!{ AllocationExplainRequest => AllocationExplanation }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterState {
+    pub cluster_name: String,
+    pub version: Option<u64>,
+    pub state_uuid: Option<String>,
+    pub master_node: Option<String>,
+    // the full response, including the requested metrics
+    pub raw: Json
+}
+
+impl FromResponse for ClusterState {
+    fn from_json(json: Json) -> EsResult<ClusterState> {
+        Ok(ClusterState {
+            cluster_name: try!(find_string(&json, "cluster_name")),
+            version: json.find("version").and_then(|v| v.as_u64()),
+            state_uuid: json.find("state_uuid").and_then(|s| s.as_string()).map(|s| s.to_string()),
+            master_node: json.find("master_node").and_then(|m| m.as_string()).map(|m| m.to_string()),
+            raw: json
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct ClusterIndicesStats {
+    pub count: u64,
+    pub docs: Option<DocsStats>,
+    pub store: Option<StoreStats>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterStats {
+    pub cluster_name: String,
+    pub status: Option<HealthStatus>,
+    pub indices: ClusterIndicesStats,
+    pub raw: Json
+}
+
+impl FromResponse for ClusterStats {
+    fn from_json(json: Json) -> EsResult<ClusterStats> {
+        let indices = try!(decode_json(try!(find_field(&json, "indices")).clone()));
+        let status = match json.find("status") {
+            Some(s) => Some(try!(decode_json(s.clone()))),
+            None => None
+        };
+
+        Ok(ClusterStats {
+            cluster_name: try!(find_string(&json, "cluster_name")),
+            status: status,
+            indices: indices,
+            raw: json
+        })
+    }
+}
+
+// Persistent and transient cluster settings, flattened to dotted keys.
+// `defaults` is only returned when `include_defaults` was requested.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterSettings {
+    pub persistent: BTreeMap<String, Json>,
+    pub transient: BTreeMap<String, Json>,
+    pub defaults: Option<BTreeMap<String, Json>>
+}
+
+impl ClusterSettings {
+    pub fn new() -> ClusterSettings {
+        ClusterSettings { persistent: BTreeMap::new(), transient: BTreeMap::new(), defaults: None }
+    }
+
+    // `Json::Null` resets a setting to its default
+    pub fn persistent<T: ToJson>(mut self, key: &str, value: T) -> ClusterSettings {
+        self.persistent.insert(key.to_string(), value.to_json());
+        self
+    }
+
+    pub fn transient<T: ToJson>(mut self, key: &str, value: T) -> ClusterSettings {
+        self.transient.insert(key.to_string(), value.to_json());
+        self
+    }
+}
+
+impl ToJson for ClusterSettings {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = BTreeMap::new();
+        obj.insert("persistent".to_string(), Json::Object(self.persistent.clone()));
+        obj.insert("transient".to_string(), Json::Object(self.transient.clone()));
+        Json::Object(obj)
+    }
+}
+
+impl FromResponse for ClusterSettings {
+    fn from_json(json: Json) -> EsResult<ClusterSettings> {
+        let section = |key: &str| -> BTreeMap<String, Json> {
+            let mut out = BTreeMap::new();
+            if let Some(s) = json.find(key) { flatten("", s, &mut out); }
+            out
+        };
+
+        Ok(ClusterSettings {
+            persistent: section("persistent"),
+            transient: section("transient"),
+            defaults: json.find("defaults").map(|_| section("defaults"))
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct PendingTask {
+    pub insert_order: u64,
+    pub priority: String,
+    pub source: String,
+    pub time_in_queue_millis: u64,
+    pub executing: Option<bool>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct PendingTasks {
+    pub tasks: Vec<PendingTask>
+}
+
+decodable_response!(PendingTasks);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllocationExplanation {
+    pub index: String,
+    pub shard: u64,
+    pub primary: bool,
+    pub current_state: Option<String>,
+    pub can_allocate: Option<String>,
+    pub allocate_explanation: Option<String>,
+    // node decisions, unassigned info and everything else
+    pub raw: Json
+}
+
+impl FromResponse for AllocationExplanation {
+    fn from_json(json: Json) -> EsResult<AllocationExplanation> {
+        let opt_string = |key: &str| json.find(key).and_then(|v| v.as_string()).map(|v| v.to_string());
+
+        Ok(AllocationExplanation {
+            index: try!(find_string(&json, "index")),
+            shard: try!(json.find("shard").and_then(|s| s.as_u64())
+                        .ok_or(Error::UnexpectedResponse("missing `shard`".to_string()))),
+            primary: json.find("primary").and_then(|p| p.as_boolean()).unwrap_or(false),
+            current_state: opt_string("current_state"),
+            can_allocate: opt_string("can_allocate"),
+            allocate_explanation: opt_string("allocate_explanation"),
+            raw: json.clone()
+        })
+    }
+}
+
+#[test]
+fn test_cluster_health_status() {
+    let body = r#"{"cluster_name": "es", "status": "yellow", "timed_out": false, "number_of_nodes": 1,
+                   "number_of_data_nodes": 1, "active_primary_shards": 5, "active_shards": 5,
+                   "relocating_shards": 0, "initializing_shards": 0, "unassigned_shards": 5}"#;
+    let health = ClusterHealth::from_response(body).unwrap();
+    assert_eq!(health.status, HealthStatus::Yellow);
+    assert_eq!(health.indices, None);
+
+    assert!(ClusterHealth::from_response(&body.replace("yellow", "purple")).is_err());
+    assert!(ClusterHealth::from_response(&body.replace("yellow", "Yellow")).is_err());
+}
+
+#[test]
+fn test_cluster_settings_round_trip() {
+    let settings = ClusterSettings::new()
+        .persistent("cluster.routing.allocation.enable", "primaries")
+        .transient("indices.recovery.max_bytes_per_sec", "50mb")
+        .transient("cluster.info.update.interval", Json::Null);
+    assert_eq!(ClusterSettings::from_json(settings.to_json()).unwrap(), settings);
+}
+
+#[test]
+fn test_cluster_settings_requests() {
+    use mock::{MockTransport, Expectation};
+
+    let settings = ClusterSettings::new()
+        .persistent("cluster.routing.allocation.enable", "primaries")
+        .transient("indices.recovery.max_bytes_per_sec", "50mb");
+
+    // the cluster answers with nested settings unless `flat_settings` is set
+    let response = r#"{"acknowledged": true,
+                       "persistent": {"cluster": {"routing": {"allocation": {"enable": "primaries"}}}},
+                       "transient": {"indices": {"recovery": {"max_bytes_per_sec": "50mb"}}}}"#;
+    let mock = MockTransport::new();
+    mock.expect(Expectation::new(Put, "/_cluster/settings").body(settings.to_json()).respond(200, response));
+    mock.expect(Expectation::new(Get, "/_cluster/settings").query("include_defaults", "true")
+                .respond(200, r#"{"persistent": {}, "transient": {}, "defaults": {"cluster": {"name": "es"}}}"#));
+
+    let client = mock.client();
+    let updated = client.cluster.put_settings(&settings).send().unwrap();
+    assert_eq!(updated.persistent.get("cluster.routing.allocation.enable"), Some(&"primaries".to_json()));
+    assert_eq!(updated.transient.get("indices.recovery.max_bytes_per_sec"), Some(&"50mb".to_json()));
+
+    let current = client.cluster.get_settings().include_defaults(true).send().unwrap();
+    assert!(current.persistent.is_empty());
+    assert_eq!(current.defaults.unwrap().get("cluster.name"), Some(&"es".to_json()));
+    mock.verify();
+}
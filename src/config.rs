@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use rustc_serialize::json::{self, Json, ToJson};
+use aliases::AliasMetadata;
+use error::{Error, EsResult};
+use mapping::Mapping;
+use settings::IndexSettings;
+
+// The settings, per-type mappings and aliases of an index, as sent when
+// creating an index or putting an index template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexConfig {
+    pub settings: Option<IndexSettings>,
+    pub mappings: BTreeMap<String, Mapping>,
+    pub aliases: BTreeMap<String, AliasMetadata>
+}
+
+impl IndexConfig {
+    pub fn new() -> IndexConfig {
+        IndexConfig {
+            settings: None,
+            mappings: BTreeMap::new(),
+            aliases: BTreeMap::new()
+        }
+    }
+
+    pub fn settings(mut self, settings: IndexSettings) -> IndexConfig {
+        self.settings = Some(settings);
+        self
+    }
+
+    pub fn mapping(mut self, typ: &str, mapping: Mapping) -> IndexConfig {
+        self.mappings.insert(typ.to_string(), mapping);
+        self
+    }
+
+    pub fn alias(mut self, name: &str, alias: AliasMetadata) -> IndexConfig {
+        self.aliases.insert(name.to_string(), alias);
+        self
+    }
+
+    pub fn to_object(&self) -> json::Object {
+        let mut obj: json::Object = BTreeMap::new();
+        if let Some(ref s) = self.settings { obj.insert("settings".to_string(), s.to_json()); }
+        if !self.mappings.is_empty() {
+            obj.insert("mappings".to_string(),
+                       Json::Object(self.mappings.iter().map(|(k, v)| (k.to_string(), v.to_json())).collect()));
+        }
+        if !self.aliases.is_empty() {
+            obj.insert("aliases".to_string(),
+                       Json::Object(self.aliases.iter().map(|(k, v)| (k.to_string(), v.to_json())).collect()));
+        }
+        obj
+    }
+
+    pub fn from_json(json: &Json) -> EsResult<IndexConfig> {
+        let mut config = IndexConfig::new();
+
+        if let Some(settings) = json.find("settings") {
+            config.settings = Some(try!(IndexSettings::from_json(settings)));
+        }
+        if let Some(mappings) = json.find("mappings") {
+            let obj = try!(mappings.as_object().ok_or(Error::UnexpectedResponse(mappings.to_string())));
+            for (typ, mapping) in obj.iter() {
+                config.mappings.insert(typ.to_string(), try!(Mapping::from_json(mapping)));
+            }
+        }
+        if let Some(aliases) = json.find("aliases") {
+            let obj = try!(aliases.as_object().ok_or(Error::UnexpectedResponse(aliases.to_string())));
+            for (name, alias) in obj.iter() {
+                config.aliases.insert(name.to_string(), AliasMetadata::from_json(alias));
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+impl ToJson for IndexConfig {
+    fn to_json(&self) -> Json {
+        Json::Object(self.to_object())
+    }
+}
+
+#[test]
+fn test_index_config_round_trip() {
+    use mapping::Field;
+
+    let mut alias = AliasMetadata::new();
+    alias.index_routing = Some("1".to_string());
+    let config = IndexConfig::new()
+        .settings(IndexSettings::new().number_of_shards(3).refresh_interval("30s"))
+        .mapping("event", Mapping::new().property("user", Field::keyword()))
+        .alias("current", alias);
+
+    let json = config.to_json();
+    assert_eq!(json.find_path(&["settings", "index.number_of_shards"]), Some(&Json::U64(3)));
+    assert_eq!(json.find_path(&["aliases", "current", "index_routing"]), Some(&"1".to_json()));
+    assert_eq!(IndexConfig::from_json(&json).unwrap(), config);
+
+    assert!(IndexConfig::new().to_object().is_empty());
+}
@@ -0,0 +1,98 @@
+use std::error;
+use std::fmt;
+use std::io;
+use hyper::HttpError;
+use rustc_serialize::json::{self, Json};
+use mapping::ParseMappingError;
+
+#[derive(Debug)]
+pub enum Error {
+    Http(HttpError),
+    Io(io::Error),
+    Parse(json::ParserError),
+    Decode(json::DecoderError),
+    Mapping(ParseMappingError),
+    // an `{"error": ..., "status": ...}` body returned by the cluster
+    Elasticsearch(Json),
+    // a connect or read timeout expired
+    Timeout(String),
+    // a non-2xx response, with its body, that isn't an elasticsearch error
+    Status(u16, String),
+    // a TLS setting couldn't be used, or the server's certificate was refused
+    Tls(String),
+    UnexpectedResponse(String)
+}
+
+pub type EsResult<T> = Result<T, Error>;
+
+impl Error {
+    pub fn is_timeout(&self) -> bool {
+        match *self {
+            Error::Timeout(_) => true,
+            _ => false
+        }
+    }
+
+    // The HTTP status of a failed response, if the request got one.
+    pub fn status(&self) -> Option<u16> {
+        match *self {
+            Error::Status(status, _) => Some(status),
+            Error::Elasticsearch(ref json) => json.find("status").and_then(|s| s.as_u64()).map(|s| s as u16),
+            _ => None
+        }
+    }
+}
+
+impl From<HttpError> for Error {
+    fn from(err: HttpError) -> Error { Error::Http(err) }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error { Error::Io(err) }
+}
+
+impl From<json::ParserError> for Error {
+    fn from(err: json::ParserError) -> Error { Error::Parse(err) }
+}
+
+impl From<json::DecoderError> for Error {
+    fn from(err: json::DecoderError) -> Error { Error::Decode(err) }
+}
+
+impl From<ParseMappingError> for Error {
+    fn from(err: ParseMappingError) -> Error { Error::Mapping(err) }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Http(ref e) => e.description(),
+            Error::Io(ref e) => e.description(),
+            Error::Parse(ref e) => e.description(),
+            Error::Decode(ref e) => e.description(),
+            Error::Mapping(ref e) => e.description(),
+            Error::Elasticsearch(_) => "elasticsearch returned an error",
+            Error::Timeout(_) => "request timed out",
+            Error::Status(..) => "unsuccessful response status",
+            Error::Tls(_) => "TLS error",
+            Error::UnexpectedResponse(_) => "unexpected response"
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Http(ref e) => write!(f, "{}", e),
+            Error::Io(ref e) => write!(f, "{}", e),
+            Error::Parse(ref e) => write!(f, "{}", e),
+            Error::Decode(ref e) => write!(f, "{}", e),
+            Error::Mapping(ref e) => write!(f, "{}", e),
+            Error::Elasticsearch(ref body) => write!(f, "elasticsearch returned an error: {}", body),
+            Error::Timeout(ref msg) => write!(f, "request timed out: {}", msg),
+            Error::Status(status, ref body) => write!(f, "unsuccessful response status {}: {}", status, body),
+            Error::Tls(ref msg) => write!(f, "TLS error: {}", msg),
+            Error::UnexpectedResponse(ref msg) => write!(f, "unexpected response: {}", msg)
+        }
+    }
+}
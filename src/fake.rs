@@ -0,0 +1,507 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::mem;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use rustc_serialize::json::{self, Json, ToJson};
+use url::form_urlencoded;
+use url::percent_encoding::lossy_utf8_percent_decode;
+use hyper::HttpResult;
+use hyper::header::ContentLength;
+use hyper::method::Method;
+use hyper::method::Method::{Get, Head, Put, Post, Delete};
+use hyper::server::{Server, Request, Response, Handler, Fresh};
+use hyper::status::StatusCode;
+use hyper::uri::RequestUri;
+use client::Client;
+
+//
+// An in-process stand-in for a cluster, for end-to-end tests over real
+// HTTP. `FakeServer::start` listens on an ephemeral local port and serves an
+// in-memory subset of the REST API:
+//
+// * documents: index, get, exists, delete and partial-document update
+// * indices: create, exists and delete (indexing creates missing indices)
+// * `_count`, `_bulk`, and `_search` with `match_all` or `term` queries
+//
+// Writes are visible immediately, so `refresh` is accepted and ignored.
+// Scripts, analysis and scoring are not supported; every hit scores 1.0.
+//
+
+pub type Reply = (u16, Json);
+
+fn object(pairs: Vec<(&str, Json)>) -> Json {
+    let mut obj: json::Object = BTreeMap::new();
+    for (key, value) in pairs.into_iter() {
+        obj.insert(key.to_string(), value);
+    }
+    Json::Object(obj)
+}
+
+fn error(status: u16, kind: &str, reason: String) -> Reply {
+    let cause = object(vec![("type", kind.to_json()), ("reason", reason.to_json())]);
+    (status, object(vec![("error", cause), ("status", status.to_json())]))
+}
+
+fn index_not_found(index: &str) -> Reply {
+    error(404, "index_not_found_exception", format!("no such index [{}]", index))
+}
+
+fn shards() -> Json {
+    object(vec![("total", 1u64.to_json()), ("successful", 1u64.to_json()), ("failed", 0u64.to_json())])
+}
+
+// Merges `doc` into `target` the way partial updates do: objects are merged
+// key by key, anything else is replaced.
+fn merge(target: &mut json::Object, doc: &json::Object) {
+    for (key, value) in doc.iter() {
+        let merged = match (target.get_mut(key), value) {
+            (Some(&mut Json::Object(ref mut inner)), &Json::Object(ref update)) => {
+                merge(inner, update);
+                true
+            },
+            _ => false
+        };
+        if !merged { target.insert(key.to_string(), value.clone()); }
+    }
+}
+
+// `match_all` (or no query) matches everything. `term` matches a field,
+// given by its dotted path, that equals the value or is an array holding it.
+fn matches(query: Option<&Json>, source: &json::Object) -> Result<bool, String> {
+    let query = match query {
+        None => return Ok(true),
+        Some(q) => q
+    };
+    if query.find("match_all").is_some() { return Ok(true); }
+
+    let term = match query.find("term").and_then(|t| t.as_object()) {
+        Some(term) if term.len() == 1 => term,
+        _ => return Err(format!("unsupported query: {}", query))
+    };
+    let (field, value) = term.iter().next().unwrap();
+    let value = value.find("value").unwrap_or(value);
+
+    let path: Vec<&str> = field.split('.').collect();
+    let found = match source.get(path[0]) {
+        Some(root) => root.find_path(&path[1..]),
+        None => None
+    };
+    Ok(match found {
+        Some(&Json::Array(ref values)) => values.contains(value),
+        Some(v) => v == value,
+        None => false
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Document {
+    source: json::Object,
+    version: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FakeIndex {
+    settings: Json,
+    mappings: Json,
+    // keyed by (type, id)
+    docs: BTreeMap<(String, String), Document>
+}
+
+impl FakeIndex {
+    fn new() -> FakeIndex {
+        FakeIndex { settings: object(vec![]), mappings: object(vec![]), docs: BTreeMap::new() }
+    }
+}
+
+// The in-memory state behind a `FakeServer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FakeCluster {
+    indices: BTreeMap<String, FakeIndex>,
+    next_id: u64
+}
+
+impl FakeCluster {
+    pub fn new() -> FakeCluster {
+        FakeCluster { indices: BTreeMap::new(), next_id: 1 }
+    }
+
+    // Answers one request, given its decoded path segments.
+    pub fn handle(&mut self, method: &Method, path: &[String], query: &[(String, String)], body: &str) -> Reply {
+        let param = |name: &str| query.iter().find(|&&(ref n, _)| n == name).map(|&(_, ref v)| v.to_string());
+        let body_json = if body.trim().is_empty() {
+            None
+        } else {
+            match Json::from_str(body) {
+                Ok(json) => Some(json),
+                // `_bulk` bodies are newline-delimited
+                Err(e) => if path.last().map(|s| &s[..]) == Some("_bulk") { None } else {
+                    return error(400, "parse_exception", format!("failed to parse body: {}", e))
+                }
+            }
+        };
+
+        let n = path.len();
+        let last = path.last().map(|s| &s[..]).unwrap_or("");
+        match (method, n, last) {
+            (&Get, 0, _) | (&Head, 0, _) =>
+                (200, object(vec![("cluster_name", "fake".to_json()),
+                                  ("version", object(vec![("number", "fake".to_json())]))])),
+            (_, _, "_bulk") if n <= 3 => {
+                let index = if n > 1 { Some(path[0].to_string()) } else { param("index") };
+                let typ = if n > 2 { Some(path[1].to_string()) } else { param("type") };
+                self.bulk(index, typ, body)
+            },
+            (&Get, _, "_count") | (&Post, _, "_count") if n <= 3 => {
+                let query = body_json.as_ref().and_then(|b| b.find("query"));
+                match self.select(&path[..n - 1], query) {
+                    Ok(docs) => (200, object(vec![("count", (docs.len() as u64).to_json()), ("_shards", shards())])),
+                    Err(reply) => reply
+                }
+            },
+            (&Get, _, "_search") | (&Post, _, "_search") if n <= 3 => {
+                let size_param = |name: &str, default: u64| body_json.as_ref()
+                    .and_then(|b| b.find(name)).and_then(|s| s.as_u64())
+                    .or(param(name).and_then(|s| u64::from_str(&s).ok()))
+                    .unwrap_or(default) as usize;
+                let (from, size) = (size_param("from", 0), size_param("size", 10));
+                let query = body_json.as_ref().and_then(|b| b.find("query"));
+                match self.select(&path[..n - 1], query) {
+                    Ok(docs) => {
+                        let total = docs.len() as u64;
+                        let hits: Vec<Json> = docs.into_iter().skip(from).take(size).map(|(index, typ, id, doc)| {
+                            object(vec![("_index", index.to_json()), ("_type", typ.to_json()), ("_id", id.to_json()),
+                                        ("_score", 1.0f64.to_json()), ("_source", Json::Object(doc.source))])
+                        }).collect();
+                        (200, object(vec![("took", 0u64.to_json()), ("timed_out", false.to_json()), ("_shards", shards()),
+                                          ("hits", object(vec![("total", total.to_json()),
+                                                               ("max_score", if total > 0 { 1.0f64.to_json() } else { Json::Null }),
+                                                               ("hits", Json::Array(hits))]))]))
+                    },
+                    Err(reply) => reply
+                }
+            },
+            (_, _, "_refresh") if n <= 2 => (200, object(vec![("_shards", shards())])),
+            (&Post, 4, "_update") => self.update(&path[0], &path[1], &path[2], body_json),
+            (&Put, 1, _) | (&Post, 1, _) => self.create_index(&path[0], body_json),
+            (&Get, 1, _) | (&Head, 1, _) => match self.indices.get(&path[0]) {
+                Some(index) => (200, object(vec![(&path[0][..], object(vec![
+                    ("aliases", object(vec![])),
+                    ("mappings", index.mappings.clone()),
+                    ("settings", index.settings.clone())]))])),
+                None => index_not_found(&path[0])
+            },
+            (&Delete, 1, _) => match self.indices.remove(&path[0]) {
+                Some(_) => (200, object(vec![("acknowledged", true.to_json())])),
+                None => index_not_found(&path[0])
+            },
+            (&Post, 2, _) if !path[1].starts_with("_") => {
+                let id = format!("fake-{}", self.next_id);
+                self.next_id += 1;
+                self.index_doc(&path[0], &path[1], &id, body_json, false)
+            },
+            (&Put, 3, _) | (&Post, 3, _) if !path[1].starts_with("_") => {
+                let create = param("op_type").map(|o| o == "create").unwrap_or(false);
+                self.index_doc(&path[0], &path[1], &path[2], body_json, create)
+            },
+            (&Get, 3, _) | (&Head, 3, _) if !path[1].starts_with("_") => self.get_doc(&path[0], &path[1], &path[2]),
+            (&Delete, 3, _) if !path[1].starts_with("_") => self.delete_doc(&path[0], &path[1], &path[2]),
+            _ => error(400, "illegal_argument_exception",
+                       format!("the fake server does not support {} {:?}", method, path))
+        }
+    }
+
+    fn create_index(&mut self, name: &str, body: Option<Json>) -> Reply {
+        if self.indices.contains_key(name) {
+            return error(400, "resource_already_exists_exception", format!("index [{}] already exists", name));
+        }
+        let mut index = FakeIndex::new();
+        if let Some(body) = body {
+            if let Some(settings) = body.find("settings") { index.settings = settings.clone(); }
+            if let Some(mappings) = body.find("mappings") { index.mappings = mappings.clone(); }
+        }
+        self.indices.insert(name.to_string(), index);
+        (200, object(vec![("acknowledged", true.to_json()), ("index", name.to_json())]))
+    }
+
+    fn doc_reply(index: &str, typ: &str, id: &str, pairs: Vec<(&str, Json)>) -> Json {
+        let mut all = vec![("_index", index.to_json()), ("_type", typ.to_json()), ("_id", id.to_json())];
+        all.extend(pairs.into_iter());
+        object(all)
+    }
+
+    fn index_doc(&mut self, index: &str, typ: &str, id: &str, body: Option<Json>, create: bool) -> Reply {
+        let source = match body {
+            Some(Json::Object(source)) => source,
+            _ => return error(400, "parse_exception", "the document source must be an object".to_string())
+        };
+
+        let docs = &mut self.indices.entry(index.to_string()).or_insert_with(FakeIndex::new).docs;
+        let key = (typ.to_string(), id.to_string());
+        let version = match docs.get(&key) {
+            Some(_) if create => return error(409, "version_conflict_engine_exception",
+                                              format!("[{}][{}]: document already exists", typ, id)),
+            Some(existing) => existing.version + 1,
+            None => 1
+        };
+        docs.insert(key, Document { source: source, version: version });
+
+        let created = version == 1;
+        (if created { 201 } else { 200 },
+         FakeCluster::doc_reply(index, typ, id, vec![
+             ("_version", version.to_json()),
+             ("created", created.to_json()),
+             ("result", (if created { "created" } else { "updated" }).to_json())]))
+    }
+
+    fn get_doc(&self, index: &str, typ: &str, id: &str) -> Reply {
+        let docs = match self.indices.get(index) {
+            Some(i) => &i.docs,
+            None => return index_not_found(index)
+        };
+        match docs.get(&(typ.to_string(), id.to_string())) {
+            Some(doc) => (200, FakeCluster::doc_reply(index, typ, id, vec![
+                ("_version", doc.version.to_json()),
+                ("found", true.to_json()),
+                ("_source", Json::Object(doc.source.clone()))])),
+            None => (404, FakeCluster::doc_reply(index, typ, id, vec![("found", false.to_json())]))
+        }
+    }
+
+    fn delete_doc(&mut self, index: &str, typ: &str, id: &str) -> Reply {
+        let docs = match self.indices.get_mut(index) {
+            Some(i) => &mut i.docs,
+            None => return index_not_found(index)
+        };
+        match docs.remove(&(typ.to_string(), id.to_string())) {
+            Some(doc) => (200, FakeCluster::doc_reply(index, typ, id, vec![
+                ("_version", (doc.version + 1).to_json()),
+                ("found", true.to_json()),
+                ("result", "deleted".to_json())])),
+            None => (404, FakeCluster::doc_reply(index, typ, id, vec![
+                ("found", false.to_json()),
+                ("result", "not_found".to_json())]))
+        }
+    }
+
+    fn update(&mut self, index: &str, typ: &str, id: &str, body: Option<Json>) -> Reply {
+        let body = body.unwrap_or(Json::Null);
+        if body.find("script").is_some() {
+            return error(400, "illegal_argument_exception", "the fake server does not run scripts".to_string());
+        }
+        let doc = match body.find("doc") {
+            Some(&Json::Object(ref doc)) => doc.clone(),
+            _ => return error(400, "action_request_validation_exception", "an update requires a `doc`".to_string())
+        };
+        let doc_as_upsert = body.find("doc_as_upsert").and_then(|d| d.as_boolean()).unwrap_or(false);
+
+        let docs = &mut self.indices.entry(index.to_string()).or_insert_with(FakeIndex::new).docs;
+        let key = (typ.to_string(), id.to_string());
+        let updated = match docs.get(&key) {
+            Some(existing) => {
+                let mut source = existing.source.clone();
+                merge(&mut source, &doc);
+                Document { source: source, version: existing.version + 1 }
+            },
+            None => match body.find("upsert") {
+                Some(&Json::Object(ref upsert)) => Document { source: upsert.clone(), version: 1 },
+                _ if doc_as_upsert => Document { source: doc, version: 1 },
+                _ => return error(404, "document_missing_exception",
+                                  format!("[{}][{}]: document missing", typ, id))
+            }
+        };
+
+        let version = updated.version;
+        docs.insert(key, updated);
+        (if version == 1 { 201 } else { 200 },
+         FakeCluster::doc_reply(index, typ, id, vec![
+             ("_version", version.to_json()),
+             ("result", (if version == 1 { "created" } else { "updated" }).to_json())]))
+    }
+
+    // Documents matching `query` in the indices (and type) named by `target`,
+    // e.g. `["logs,metrics", "event"]`, `["_all"]` or `[]`.
+    fn select(&self, target: &[String], query: Option<&Json>) -> Result<Vec<(String, String, String, Document)>, Reply> {
+        let names: Vec<String> = match target.first() {
+            None => self.indices.keys().cloned().collect(),
+            Some(t) if &t[..] == "_all" => self.indices.keys().cloned().collect(),
+            Some(t) => t.split(',').map(|s| s.to_string()).collect()
+        };
+        let typ = target.get(1);
+
+        let mut out = Vec::new();
+        for name in names.iter() {
+            let index = match self.indices.get(name) {
+                Some(index) => index,
+                None => return Err(index_not_found(name))
+            };
+            for (&(ref t, ref id), doc) in index.docs.iter() {
+                if typ.map(|typ| typ != t).unwrap_or(false) { continue; }
+                match matches(query, &doc.source) {
+                    Ok(true) => out.push((name.to_string(), t.to_string(), id.to_string(), doc.clone())),
+                    Ok(false) => {},
+                    Err(reason) => return Err(error(400, "query_parsing_exception", reason))
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn bulk(&mut self, index: Option<String>, typ: Option<String>, body: &str) -> Reply {
+        let mut lines = body.lines().filter(|l| !l.trim().is_empty());
+        let mut items = Vec::new();
+        let mut errors = false;
+
+        while let Some(line) = lines.next() {
+            let action = match Json::from_str(line) {
+                Ok(Json::Object(action)) if action.len() == 1 => action,
+                _ => return error(400, "illegal_argument_exception", format!("malformed bulk action: {}", line))
+            };
+            let (op, meta) = action.into_iter().next().unwrap();
+            let field = |name: &str, default: &Option<String>| meta.find(name).and_then(|v| v.as_string())
+                .map(|v| v.to_string()).or(default.clone());
+            let (i, t) = match (field("_index", &index), field("_type", &typ)) {
+                (Some(i), Some(t)) => (i, t),
+                _ => return error(400, "action_request_validation_exception",
+                                  format!("bulk action without an index and type: {}", line))
+            };
+            let id = field("_id", &None);
+
+            let source = if &op[..] == "delete" { None } else {
+                match lines.next() {
+                    Some(source) => match Json::from_str(source) {
+                        Ok(source) => Some(source),
+                        Err(e) => return error(400, "parse_exception", format!("malformed bulk source: {}", e))
+                    },
+                    None => return error(400, "illegal_argument_exception", "bulk action without a source".to_string())
+                }
+            };
+
+            let (status, reply) = match (&op[..], id) {
+                ("index", Some(id)) => self.index_doc(&i, &t, &id, source, false),
+                ("create", Some(id)) => self.index_doc(&i, &t, &id, source, true),
+                ("index", None) | ("create", None) => {
+                    let id = format!("fake-{}", self.next_id);
+                    self.next_id += 1;
+                    self.index_doc(&i, &t, &id, source, false)
+                },
+                ("update", Some(id)) => self.update(&i, &t, &id, source),
+                ("delete", Some(id)) => self.delete_doc(&i, &t, &id),
+                _ => error(400, "illegal_argument_exception", format!("unsupported bulk action: {}", line))
+            };
+
+            let mut item = match reply {
+                Json::Object(obj) => obj,
+                _ => BTreeMap::new()
+            };
+            item.insert("status".to_string(), status.to_json());
+            if status >= 300 && !(status == 404 && &op[..] == "delete") { errors = true; }
+            items.push(object(vec![(&op[..], Json::Object(item))]));
+        }
+
+        (200, object(vec![("took", 0u64.to_json()), ("errors", errors.to_json()), ("items", Json::Array(items))]))
+    }
+}
+
+struct FakeHandler {
+    cluster: Arc<Mutex<FakeCluster>>
+}
+
+impl Handler for FakeHandler {
+    fn handle<'a, 'k>(&'a self, mut req: Request<'a, 'k>, mut res: Response<'a, Fresh>) {
+        let target = match req.uri {
+            RequestUri::AbsolutePath(ref p) => p.to_string(),
+            _ => "/".to_string()
+        };
+        let (path, query) = match target.find('?') {
+            Some(i) => (&target[..i], form_urlencoded::parse(target[i + 1..].as_bytes())),
+            None => (&target[..], Vec::new())
+        };
+        let segments: Vec<String> = path.split('/').filter(|s| !s.is_empty())
+            .map(|s| lossy_utf8_percent_decode(s.as_bytes())).collect();
+
+        let mut body = String::new();
+        let _ = req.read_to_string(&mut body);
+
+        let (status, reply) = self.cluster.lock().unwrap().handle(&req.method, &segments, &query, &body);
+        let bytes = if req.method == Head { Vec::new() } else { reply.to_string().into_bytes() };
+
+        *res.status_mut() = StatusCode::from_u16(status);
+        res.headers_mut().set(ContentLength(bytes.len() as u64));
+        res.headers_mut().set_raw("Content-Type", vec![b"application/json".to_vec()]);
+        if let Ok(mut res) = res.start() {
+            let _ = res.write_all(&bytes);
+            let _ = res.end();
+        }
+    }
+}
+
+// A running fake cluster. The server thread lives until the process exits:
+// hyper joins its accept loop when the listener is dropped, which would
+// never return.
+#[derive(Clone)]
+pub struct FakeServer {
+    addr: SocketAddr,
+    cluster: Arc<Mutex<FakeCluster>>
+}
+
+impl FakeServer {
+    pub fn start() -> HttpResult<FakeServer> {
+        let cluster = Arc::new(Mutex::new(FakeCluster::new()));
+        let listening = try!(Server::http(FakeHandler { cluster: cluster.clone() }).listen("127.0.0.1:0"));
+        let addr = listening.socket;
+        mem::forget(listening);
+        Ok(FakeServer { addr: addr, cluster: cluster })
+    }
+
+    // e.g. "http://127.0.0.1:49152"
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn client(&self) -> Client {
+        Client::new_with_str_host(&self.url())
+    }
+
+    // Drops every index.
+    pub fn reset(&self) {
+        *self.cluster.lock().unwrap() = FakeCluster::new();
+    }
+}
+
+#[test]
+fn test_fake_server() {
+    use config::IndexConfig;
+    use transport::RequestOptions;
+
+    let server = FakeServer::start().unwrap();
+    let client = server.client();
+    let parse = |body: String| Json::from_str(&body).unwrap();
+
+    let created = parse(client.indices.create("logs", &IndexConfig::new()).execute().unwrap());
+    assert_eq!(created.find("acknowledged"), Some(&Json::Boolean(true)));
+
+    let mut source: json::Object = BTreeMap::new();
+    source.insert("user".to_string(), "kimchy".to_json());
+    let indexed = parse(client.index("logs", "event", Some("1".to_string()), source).execute().unwrap());
+    assert_eq!(indexed.find("_version"), Some(&Json::U64(1)));
+
+    let doc = parse(client.get("logs", "event", "1").execute().unwrap());
+    assert_eq!(doc.find_path(&["_source", "user"]), Some(&"kimchy".to_json()));
+
+    let count = parse(client.count(Some("logs".to_string()), None).execute().unwrap());
+    assert_eq!(count.find("count"), Some(&Json::U64(1)));
+
+    let query = r#"{"query": {"term": {"user": "kimchy"}}}"#;
+    let hits = parse(client.connection.request(Post, vec!["logs".to_string(), "_search".to_string()],
+                                               Vec::new(), Some(query.as_bytes()), &RequestOptions::new()).unwrap());
+    assert_eq!(hits.find_path(&["hits", "total"]), Some(&Json::U64(1)));
+
+    let deleted = parse(client.delete("logs", "event", "1").execute().unwrap());
+    assert_eq!(deleted.find("found"), Some(&Json::Boolean(true)));
+
+    assert_eq!(client.exists("logs", "event", "1").send().unwrap(), false);
+    let missing = client.get("logs", "event", "1").execute().unwrap_err();
+    assert_eq!(missing.status(), Some(404));
+}
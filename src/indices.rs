@@ -1,23 +1,30 @@
+use std::collections::BTreeMap;
 use types::*;
 use rustc_serialize::json;
-use hyper::HttpResult;
-use hyper::method::Method::{Post, Head};
+use hyper::method::Method::{Get, Put, Post, Head, Delete};
 use connection::Connection;
+use error::EsResult;
+use response::{FromResponse, AcknowledgedResponse, ShardsResponse};
+use settings::IndexSettingsEntry;
+use aliases::AliasMetadata;
+use templates::IndexTemplate;
+use rollover::{RolloverResponse, ResizeResponse};
+use analyze::AnalyzeResponse;
+use stats::{IndicesStatsResponse, SegmentsResponse, RecoveryResponse};
 
-// new_query_struct!{ AnalyzeRequest(index: Option<String>) {
-//     fn_path => |self| {
-//         if let Some(ref i) = self.index {
-//             vec![i.to_string()]
-//         } else {
-//             vec![]
-//         }
-//     },
-//     query_params => [
-//         (source: Source, None),
-//         (analyzer: Analyzer, None)
-//     ],
-//     method => Get
-// }}
+new_query_struct!{ AnalyzeRequest(index: Option<String>, analyze_body: json::Object) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if let Some(ref index) = self.index { path.push(index.to_string()); }
+        path.push("_analyze".to_string());
+        path
+    },
+    query_params => [],
+    body => analyze_body,
+    method => Post
+}}
+
+impl_send!{ AnalyzeRequest => AnalyzeResponse }
 
 new_query_struct!{ ExistsRequest(indices: StringList) {
     fn_path => |self| {
@@ -32,7 +39,9 @@ new_query_struct!{ ExistsRequest(indices: StringList) {
     method => Head
 }}
 
-new_query_struct!{ CreateRequest(index: String) {
+impl_exists!{ ExistsRequest }
+
+new_query_struct!{ CreateRequest(index: String, config_body: json::Object) {
     fn_path => |self| {
         vec![self.index.to_string()]
     },
@@ -43,6 +52,7 @@ new_query_struct!{ CreateRequest(index: String) {
         (allow_no_indices: AllowNoIndices, None),
         (expand_wildcards: ExpandWildcards, None)
     ],
+    body => config_body,
     method => Post
 }}
 
@@ -57,3 +67,395 @@ new_query_struct!{ CloseRequest(index: String, config_body: json::Object) {
     body => config_body,
     method => Post
 }}
+
+new_query_struct!{ PutMappingRequest(indices: StringList, typ: String, mapping: json::Json) {
+    fn_path => |self| {
+        vec![self.indices.to_string(), "_mapping".to_string(), self.typ.to_string()]
+    },
+    query_params => [
+        (timeout: Timeout, None),
+        (master_timeout: MasterTimeout, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    body => mapping,
+    method => Put
+}}
+
+new_query_struct!{ GetMappingRequest(indices: Option<StringList>, typ: Option<String>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if let Some(ref indices) = self.indices { path.push(indices.to_string()); }
+        path.push("_mapping".to_string());
+        if let Some(ref typ) = self.typ { path.push(typ.to_string()); }
+        path
+    },
+    query_params => [
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None),
+        (local: Local, None)
+    ],
+    method => Get
+}}
+
+new_query_struct!{ GetSettingsRequest(indices: Option<StringList>, name: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if let Some(ref indices) = self.indices { path.push(indices.to_string()); }
+        path.push("_settings".to_string());
+        if let Some(ref name) = self.name { path.push(name.to_string()); }
+        path
+    },
+    query_params => [
+        (flat_settings: FlatSettings, None),
+        (include_defaults: IncludeDefaults, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None),
+        (local: Local, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ GetSettingsRequest => BTreeMap<String, IndexSettingsEntry> }
+
+new_query_struct!{ UpdateSettingsRequest(indices: StringList, settings: json::Json) {
+    fn_path => |self| {
+        vec![self.indices.to_string(), "_settings".to_string()]
+    },
+    query_params => [
+        (preserve_existing: PreserveExisting, None),
+        (flat_settings: FlatSettings, None),
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    body => settings,
+    method => Put
+}}
+
+impl_send!{ UpdateSettingsRequest => AcknowledgedResponse }
+
+new_query_struct!{ RefreshRequest(indices: StringList) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if !self.indices.0.is_empty() { path.push(self.indices.to_string()); }
+        path.push("_refresh".to_string());
+        path
+    },
+    query_params => [
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    method => Post
+}}
+
+impl_send!{ RefreshRequest => ShardsResponse }
+
+new_query_struct!{ ForceMergeRequest(indices: StringList) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if !self.indices.0.is_empty() { path.push(self.indices.to_string()); }
+        path.push("_forcemerge".to_string());
+        path
+    },
+    query_params => [
+        (max_num_segments: MaxNumSegments, None),
+        (only_expunge_deletes: OnlyExpungeDeletes, None),
+        (flush: Flush, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    method => Post
+}}
+
+impl_send!{ ForceMergeRequest => ShardsResponse }
+
+new_query_struct!{ FlushRequest(indices: StringList) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if !self.indices.0.is_empty() { path.push(self.indices.to_string()); }
+        path.push("_flush".to_string());
+        path
+    },
+    query_params => [
+        (force: Force, None),
+        (wait_if_ongoing: WaitIfOngoing, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    method => Post
+}}
+
+impl_send!{ FlushRequest => ShardsResponse }
+
+new_query_struct!{ ClearCacheRequest(indices: StringList) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if !self.indices.0.is_empty() { path.push(self.indices.to_string()); }
+        path.push("_cache".to_string());
+        path.push("clear".to_string());
+        path
+    },
+    query_params => [
+        (query: QueryCache, None),
+        (request: RequestCache, None),
+        (fielddata: Fielddata, None),
+        (fields: Fields, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    method => Post
+}}
+
+impl_send!{ ClearCacheRequest => ShardsResponse }
+
+new_query_struct!{ GetAliasRequest(indices: Option<StringList>, name: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if let Some(ref indices) = self.indices { path.push(indices.to_string()); }
+        path.push("_alias".to_string());
+        if let Some(ref name) = self.name { path.push(name.to_string()); }
+        path
+    },
+    query_params => [
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None),
+        (local: Local, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ GetAliasRequest => BTreeMap<String, BTreeMap<String, AliasMetadata>> }
+
+new_query_struct!{ PutAliasRequest(indices: StringList, name: String, alias_body: json::Object) {
+    fn_path => |self| {
+        vec![self.indices.to_string(), "_alias".to_string(), self.name.to_string()]
+    },
+    query_params => [
+        (timeout: Timeout, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    body => alias_body,
+    method => Put
+}}
+
+impl_send!{ PutAliasRequest => AcknowledgedResponse }
+
+new_query_struct!{ DeleteAliasRequest(indices: StringList, name: StringList) {
+    fn_path => |self| {
+        vec![self.indices.to_string(), "_alias".to_string(), self.name.to_string()]
+    },
+    query_params => [
+        (timeout: Timeout, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    method => Delete
+}}
+
+impl_send!{ DeleteAliasRequest => AcknowledgedResponse }
+
+new_query_struct!{ UpdateAliasesRequest(actions: json::Object) {
+    fn_path => |self| {
+        vec!["_aliases".to_string()]
+    },
+    query_params => [
+        (timeout: Timeout, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    body => actions,
+    method => Post
+}}
+
+impl_send!{ UpdateAliasesRequest => AcknowledgedResponse }
+
+new_query_struct!{ PutTemplateRequest(name: String, template_body: json::Object) {
+    fn_path => |self| {
+        vec!["_template".to_string(), self.name.to_string()]
+    },
+    query_params => [
+        (flat_settings: FlatSettings, None),
+        (timeout: Timeout, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    body => template_body,
+    method => Put
+}}
+
+impl_send!{ PutTemplateRequest => AcknowledgedResponse }
+
+new_query_struct!{ GetTemplateRequest(name: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_template".to_string()];
+        if let Some(ref name) = self.name { path.push(name.to_string()); }
+        path
+    },
+    query_params => [
+        (flat_settings: FlatSettings, None),
+        (master_timeout: MasterTimeout, None),
+        (local: Local, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ GetTemplateRequest => BTreeMap<String, IndexTemplate> }
+
+new_query_struct!{ DeleteTemplateRequest(name: String) {
+    fn_path => |self| {
+        vec!["_template".to_string(), self.name.to_string()]
+    },
+    query_params => [
+        (timeout: Timeout, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    method => Delete
+}}
+
+impl_send!{ DeleteTemplateRequest => AcknowledgedResponse }
+
+new_query_struct!{ ExistsTemplateRequest(name: StringList) {
+    fn_path => |self| {
+        vec!["_template".to_string(), self.name.to_string()]
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None),
+        (local: Local, None)
+    ],
+    method => Head
+}}
+
+impl_exists!{ ExistsTemplateRequest }
+
+new_query_struct!{ RolloverRequest(alias: String, new_index: Option<String>, rollover_body: json::Object) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec![self.alias.to_string(), "_rollover".to_string()];
+        if let Some(ref new_index) = self.new_index { path.push(new_index.to_string()); }
+        path
+    },
+    query_params => [
+        (dry_run: DryRun, None),
+        (wait_for_active_shards: WaitForActiveShards, None),
+        (timeout: Timeout, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    body => rollover_body,
+    method => Post
+}}
+
+impl_send!{ RolloverRequest => RolloverResponse }
+
+new_query_struct!{ ShrinkRequest(index: String, target: String, config_body: json::Object) {
+    fn_path => |self| {
+        vec![self.index.to_string(), "_shrink".to_string(), self.target.to_string()]
+    },
+    query_params => [
+        (wait_for_active_shards: WaitForActiveShards, None),
+        (timeout: Timeout, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    body => config_body,
+    method => Post
+}}
+
+impl_send!{ ShrinkRequest => ResizeResponse }
+
+new_query_struct!{ SplitRequest(index: String, target: String, config_body: json::Object) {
+    fn_path => |self| {
+        vec![self.index.to_string(), "_split".to_string(), self.target.to_string()]
+    },
+    query_params => [
+        (wait_for_active_shards: WaitForActiveShards, None),
+        (timeout: Timeout, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    body => config_body,
+    method => Post
+}}
+
+impl_send!{ SplitRequest => ResizeResponse }
+
+new_query_struct!{ StatsRequest(indices: StringList, metric: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if !self.indices.0.is_empty() { path.push(self.indices.to_string()); }
+        path.push("_stats".to_string());
+        if let Some(ref metric) = self.metric { path.push(metric.to_string()); }
+        path
+    },
+    query_params => [
+        (level: Level, None),
+        (fields: Fields, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ StatsRequest => IndicesStatsResponse }
+
+new_query_struct!{ SegmentsRequest(indices: StringList) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if !self.indices.0.is_empty() { path.push(self.indices.to_string()); }
+        path.push("_segments".to_string());
+        path
+    },
+    query_params => [
+        (verbose: Verbose, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ SegmentsRequest => SegmentsResponse }
+
+new_query_struct!{ RecoveryRequest(indices: StringList) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if !self.indices.0.is_empty() { path.push(self.indices.to_string()); }
+        path.push("_recovery".to_string());
+        path
+    },
+    query_params => [
+        (detailed: Detailed, None),
+        (active_only: ActiveOnly, None),
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ RecoveryRequest => RecoveryResponse }
+
+#[test]
+fn test_flush_and_clear_cache() {
+    use mock::{MockTransport, Expectation};
+
+    let shards = r#"{"_shards": {"total": 4, "successful": 4, "failed": 0}}"#;
+    let mock = MockTransport::new();
+    mock.expect(Expectation::new(Post, "/logs,metrics/_flush")
+                .query("force", "true").query("wait_if_ongoing", "true").respond(200, shards));
+    mock.expect(Expectation::new(Post, "/logs/_cache/clear")
+                .query("fielddata", "true").query("fields", "user,host").respond(200, shards));
+    mock.expect(Expectation::new(Post, "/_cache/clear").no_query().respond(200, shards));
+
+    let client = mock.client();
+    let flushed = client.indices.flush(string_list!["logs", "metrics"]).force(true).wait_if_ongoing(true).send().unwrap();
+    assert_eq!(flushed.shards.successful, 4);
+    client.indices.clear_cache(string_list!["logs"]).fielddata(true).fields(string_list!["user", "host"]).send().unwrap();
+    client.indices.clear_cache(string_list![]).send().unwrap();
+    mock.verify();
+}
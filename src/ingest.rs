@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use rustc_serialize::json::{self, Json, ToJson};
+use hyper::method::Method::{Get, Put, Post, Delete};
+use connection::Connection;
+use error::{Error, EsResult};
+use response::{FromResponse, AcknowledgedResponse};
+use types::*;
+
+new_query_struct!{ PutPipelineRequest(id: String, pipeline_body: json::Json) {
+    fn_path => |self| {
+        vec!["_ingest".to_string(), "pipeline".to_string(), self.id.to_string()]
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    body => pipeline_body,
+    method => Put
+}}
+
+impl_send!{ PutPipelineRequest => AcknowledgedResponse }
+
+new_query_struct!{ GetPipelineRequest(id: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_ingest".to_string(), "pipeline".to_string()];
+        if let Some(ref id) = self.id { path.push(id.to_string()); }
+        path
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ GetPipelineRequest => BTreeMap<String, IngestPipeline> }
+
+new_query_struct!{ DeletePipelineRequest(id: String) {
+    fn_path => |self| {
+        vec!["_ingest".to_string(), "pipeline".to_string(), self.id.to_string()]
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    method => Delete
+}}
+
+impl_send!{ DeletePipelineRequest => AcknowledgedResponse }
+
+// Runs the stored pipeline `id`, or the pipeline given in the body, against
+// the documents in the body without indexing them.
+new_query_struct!{ SimulatePipelineRequest(id: Option<String>, simulate_body: json::Object) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_ingest".to_string(), "pipeline".to_string()];
+        if let Some(ref id) = self.id { path.push(id.to_string()); }
+        path.push("_simulate".to_string());
+        path
+    },
+    query_params => [
+        (verbose: Verbose, None)
+    ],
+    body => simulate_body,
+    method => Post
+}}
+
+impl_send!{ SimulatePipelineRequest => SimulateResponse }
+
+// A single processor, serialized as `{"<kind>": {<options>}}`. The
+// constructors cover the required options of common processors; anything
+// else can be added with `option`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Processor {
+    pub kind: String,
+    pub options: json::Object
+}
+
+impl Processor {
+    pub fn new(kind: &str) -> Processor {
+        Processor { kind: kind.to_string(), options: BTreeMap::new() }
+    }
+
+    pub fn set<T: ToJson>(field: &str, value: T) -> Processor {
+        Processor::new("set").option("field", field).option("value", value)
+    }
+
+    pub fn remove(fields: Vec<String>) -> Processor {
+        Processor::new("remove").option("field", fields)
+    }
+
+    pub fn rename(field: &str, target_field: &str) -> Processor {
+        Processor::new("rename").option("field", field).option("target_field", target_field)
+    }
+
+    pub fn grok(field: &str, patterns: Vec<String>) -> Processor {
+        Processor::new("grok").option("field", field).option("patterns", patterns)
+    }
+
+    pub fn date(field: &str, formats: Vec<String>) -> Processor {
+        Processor::new("date").option("field", field).option("formats", formats)
+    }
+
+    // `convert_type` is one of integer, long, float, double, string, boolean or auto
+    pub fn convert(field: &str, convert_type: &str) -> Processor {
+        Processor::new("convert").option("field", field).option("type", convert_type)
+    }
+
+    pub fn script(source: &str) -> Processor {
+        Processor::new("script").option("source", source)
+    }
+
+    pub fn lowercase(field: &str) -> Processor {
+        Processor::new("lowercase").option("field", field)
+    }
+
+    pub fn uppercase(field: &str) -> Processor {
+        Processor::new("uppercase").option("field", field)
+    }
+
+    pub fn trim(field: &str) -> Processor {
+        Processor::new("trim").option("field", field)
+    }
+
+    pub fn split(field: &str, separator: &str) -> Processor {
+        Processor::new("split").option("field", field).option("separator", separator)
+    }
+
+    pub fn option<T: ToJson>(mut self, key: &str, value: T) -> Processor {
+        self.options.insert(key.to_string(), value.to_json());
+        self
+    }
+
+    pub fn tag(self, tag: &str) -> Processor {
+        self.option("tag", tag)
+    }
+
+    pub fn ignore_failure(self, ignore_failure: bool) -> Processor {
+        self.option("ignore_failure", ignore_failure)
+    }
+
+    pub fn on_failure(self, processors: Vec<Processor>) -> Processor {
+        self.option("on_failure", Json::Array(processors.iter().map(|p| p.to_json()).collect()))
+    }
+
+    fn from_json(json: &Json) -> EsResult<Processor> {
+        match json.as_object() {
+            Some(obj) if obj.len() == 1 => {
+                let (kind, options) = obj.iter().next().unwrap();
+                match options.as_object() {
+                    Some(options) => Ok(Processor { kind: kind.to_string(), options: options.clone() }),
+                    None => Err(Error::UnexpectedResponse(format!("processor options: {}", options)))
+                }
+            },
+            _ => Err(Error::UnexpectedResponse(format!("processor: {}", json)))
+        }
+    }
+}
+
+impl ToJson for Processor {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = BTreeMap::new();
+        obj.insert(self.kind.to_string(), Json::Object(self.options.clone()));
+        Json::Object(obj)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngestPipeline {
+    pub description: Option<String>,
+    pub version: Option<i64>,
+    pub processors: Vec<Processor>,
+    pub on_failure: Vec<Processor>
+}
+
+impl IngestPipeline {
+    pub fn new(processors: Vec<Processor>) -> IngestPipeline {
+        IngestPipeline { description: None, version: None, processors: processors, on_failure: Vec::new() }
+    }
+
+    pub fn description(mut self, description: &str) -> IngestPipeline {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn version(mut self, version: i64) -> IngestPipeline {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn on_failure(mut self, processors: Vec<Processor>) -> IngestPipeline {
+        self.on_failure = processors;
+        self
+    }
+
+    pub fn from_json(json: &Json) -> EsResult<IngestPipeline> {
+        let processors = |key: &str| -> EsResult<Vec<Processor>> {
+            let mut out = Vec::new();
+            if let Some(list) = json.find(key).and_then(|p| p.as_array()) {
+                for p in list.iter() {
+                    out.push(try!(Processor::from_json(p)));
+                }
+            }
+            Ok(out)
+        };
+
+        Ok(IngestPipeline {
+            description: json.find("description").and_then(|d| d.as_string()).map(|d| d.to_string()),
+            version: json.find("version").and_then(|v| v.as_i64()),
+            processors: try!(processors("processors")),
+            on_failure: try!(processors("on_failure"))
+        })
+    }
+}
+
+impl ToJson for IngestPipeline {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = BTreeMap::new();
+        if let Some(ref d) = self.description { obj.insert("description".to_string(), d.to_json()); }
+        if let Some(v) = self.version { obj.insert("version".to_string(), v.to_json()); }
+        obj.insert("processors".to_string(), Json::Array(self.processors.iter().map(|p| p.to_json()).collect()));
+        if !self.on_failure.is_empty() {
+            obj.insert("on_failure".to_string(), Json::Array(self.on_failure.iter().map(|p| p.to_json()).collect()));
+        }
+        Json::Object(obj)
+    }
+}
+
+// keyed by pipeline id
+impl FromResponse for BTreeMap<String, IngestPipeline> {
+    fn from_json(json: Json) -> EsResult<BTreeMap<String, IngestPipeline>> {
+        let pipelines = match json {
+            Json::Object(obj) => obj,
+            other => return Err(Error::UnexpectedResponse(other.to_string()))
+        };
+
+        let mut out = BTreeMap::new();
+        for (id, value) in pipelines.iter() {
+            out.insert(id.to_string(), try!(IngestPipeline::from_json(value)));
+        }
+        Ok(out)
+    }
+}
+
+// `pipeline` is only needed when simulating a pipeline that is not stored.
+pub fn simulate_body(pipeline: Option<&IngestPipeline>, docs: Vec<json::Object>) -> json::Object {
+    let mut obj: json::Object = BTreeMap::new();
+    if let Some(p) = pipeline { obj.insert("pipeline".to_string(), p.to_json()); }
+    obj.insert("docs".to_string(), Json::Array(docs.into_iter().map(|source| {
+        let mut doc: json::Object = BTreeMap::new();
+        doc.insert("_source".to_string(), Json::Object(source));
+        Json::Object(doc)
+    }).collect()));
+    obj
+}
+
+// With `verbose`, `processor_results` holds the document after each
+// processor and `source` is `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedDocument {
+    pub source: Option<Json>,
+    pub processor_results: Vec<Json>,
+    pub error: Option<Json>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulateResponse {
+    pub docs: Vec<SimulatedDocument>
+}
+
+impl FromResponse for SimulateResponse {
+    fn from_json(json: Json) -> EsResult<SimulateResponse> {
+        let mut docs = Vec::new();
+        if let Some(list) = json.find("docs").and_then(|d| d.as_array()) {
+            for d in list.iter() {
+                docs.push(SimulatedDocument {
+                    source: d.find_path(&["doc", "_source"]).map(|s| s.clone()),
+                    processor_results: match d.find("processor_results").and_then(|p| p.as_array()) {
+                        Some(results) => results.clone(),
+                        None => Vec::new()
+                    },
+                    error: d.find("error").map(|e| e.clone())
+                });
+            }
+        }
+        Ok(SimulateResponse { docs: docs })
+    }
+}
+
+#[test]
+fn test_pipeline_round_trip() {
+    let pipeline = IngestPipeline::new(vec![
+        Processor::grok("message", vec!["%{IP:client} %{WORD:method}".to_string()]),
+        Processor::convert("bytes", "long").ignore_failure(true),
+        Processor::remove(vec!["message".to_string()]).tag("cleanup")
+    ]).description("access logs").version(2);
+
+    assert_eq!(IngestPipeline::from_json(&pipeline.to_json()).unwrap(), pipeline);
+}
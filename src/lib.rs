@@ -7,6 +7,7 @@
 #![plugin(regex_macros)]
 
 extern crate hyper;
+extern crate openssl;
 extern crate rustc_serialize;
 extern crate url;
 extern crate chrono;
@@ -19,7 +20,29 @@ mod macros;
 
 mod types;
 mod connection;
+pub mod transport;
+pub mod retry;
+pub mod auth;
+pub mod tls;
+pub mod mock;
+pub mod fake;
+pub mod error;
+pub mod response;
 pub mod actions;
 pub mod indices;
+pub mod aliases;
+pub mod analyze;
+pub mod config;
+pub mod templates;
+pub mod rollover;
+pub mod stats;
+pub mod cluster;
+pub mod nodes;
+pub mod cat;
+pub mod snapshot;
+pub mod ingest;
+pub mod scripts;
+pub mod mapping;
+pub mod settings;
 pub mod client;
 
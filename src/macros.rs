@@ -40,13 +40,51 @@ macro_rules! param_pairs {
 #[macro_export]
 macro_rules! field_setter {
     ($c: ty , ($field: ident, $t: ty)) => {
-        pub fn $field<T: Into<$t>>(&'a mut self, $field: T) -> &'a mut $c {
+        pub fn $field<T: Into<$t>>(&mut self, $field: T) -> &mut Self {
             self.$field = Some($field.into());
             self
         }
     }
 }
 
+// Per-request overrides of the connection's settings, for request structs
+// with a `request_options` field.
+#[macro_export]
+macro_rules! request_option_setters {
+    () => {
+        pub fn connect_timeout(&mut self, timeout: ::chrono::Duration) -> &mut Self {
+            self.request_options.timeouts.connect = Some(timeout);
+            self
+        }
+
+        pub fn read_timeout(&mut self, timeout: ::chrono::Duration) -> &mut Self {
+            self.request_options.timeouts.read = Some(timeout);
+            self
+        }
+
+        // Lets the retry policy retry this request even if it is a POST,
+        // e.g. an index request without an id that is safe to repeat.
+        pub fn retry_non_idempotent(&mut self) -> &mut Self {
+            self.request_options.retry_non_idempotent = true;
+            self
+        }
+
+        // Sends a header with this request, replacing a default header of
+        // the same name. `Authorization` is ignored if the connection has
+        // credentials.
+        pub fn header(&mut self, name: &str, value: &str) -> &mut Self {
+            ::transport::set_header(&mut self.request_options.headers, name, value);
+            self
+        }
+
+        // Tags the request so it can be traced in the slow logs and tasks
+        // api of the cluster.
+        pub fn opaque_id(&mut self, id: &str) -> &mut Self {
+            self.header("X-Opaque-Id", id)
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! impl_query_param {
     ($param_type: ty , $name:expr, { |$re:ident| $str_expr:expr }) => {
@@ -62,12 +100,24 @@ macro_rules! new_query_struct {
     ( $c:ident ( $($req_field:ident : $req_type:ty),* ) { fn_path => | $path_x:ident | $fn_path:block , query_params
         => [ $(($opt_field:ident : $opt_type:ty , $opt_default:expr)),* ] , body =>
         $body:ident , method => $method:ident } ) => {
+            new_query_struct!{ $c ( $($req_field : $req_type),* ) {
+                fn_path => | $path_x | $fn_path,
+                query_params => [ $(($opt_field : $opt_type , $opt_default)),* ],
+                body => $body,
+                fn_method => | self | { $method }
+            }}
+    };
+    // the method can depend on the request, like the path
+    ( $c:ident ( $($req_field:ident : $req_type:ty),* ) { fn_path => | $path_x:ident | $fn_path:block , query_params
+        => [ $(($opt_field:ident : $opt_type:ty , $opt_default:expr)),* ] , body =>
+        $body:ident , fn_method => | $method_x:ident | $fn_method:block } ) => {
 
             #[derive(Debug, Clone, PartialEq)]
             pub struct $c<'a> {
                 connection: &'a Connection,
                 $( $req_field: $req_type,)*
                 $( $opt_field: Option<$opt_type>,)*
+                request_options: ::transport::RequestOptions
             }
 
             impl<'a> $c<'a> {
@@ -76,6 +126,7 @@ macro_rules! new_query_struct {
                         connection: connection,
                         $( $req_field: $req_field,)*
                         $( $opt_field: $opt_default,)*
+                        request_options: ::transport::RequestOptions::new()
                     }
                 }
 
@@ -85,14 +136,23 @@ macro_rules! new_query_struct {
                     field_setter!{ $c , ($opt_field, $opt_type) }
                 )*
 
+                request_option_setters!{}
+
                 pub fn get_path(&$path_x) -> Vec<String> $fn_path
 
-                pub fn execute(&self) -> HttpResult<String> {
+                pub fn get_method(&$method_x) -> ::hyper::method::Method $fn_method
+
+                pub fn response(&self) -> ::error::EsResult<::transport::TransportResponse> {
                     let params: Vec<(&str, String)> = param_pairs! {
                         $(self.$opt_field),*
                     };
                     let bod: String = json::encode(&self.$body).ok().expect(":(");
-                    self.connection.request($method, self.get_path(), params, Some(bod.as_bytes()))
+                    self.connection.send(self.get_method(), self.get_path(), params, Some(bod.as_bytes()),
+                                         &self.request_options)
+                }
+
+                pub fn execute(&self) -> ::error::EsResult<String> {
+                    self.response().and_then(|r| r.into_body())
                 }
             }
     };
@@ -104,6 +164,7 @@ macro_rules! new_query_struct {
                 connection: &'a Connection,
                 $( $req_field: $req_type,)*
                 $( $opt_field: Option<$opt_type>,)*
+                request_options: ::transport::RequestOptions
             }
 
             impl<'a> $c<'a> {
@@ -112,6 +173,7 @@ macro_rules! new_query_struct {
                         connection: connection,
                         $( $req_field: $req_field,)*
                         $( $opt_field: $opt_default,)*
+                        request_options: ::transport::RequestOptions::new()
                     }
                 }
 
@@ -121,13 +183,19 @@ macro_rules! new_query_struct {
                     field_setter!{ $c , ($opt_field, $opt_type) }
                 )*
 
+                request_option_setters!{}
+
                 pub fn get_path(&$path_x) -> Vec<String> $fn_path
 
-                pub fn execute(&self) -> HttpResult<String> {
+                pub fn response(&self) -> ::error::EsResult<::transport::TransportResponse> {
                     let params: Vec<(&str, String)> = param_pairs! {
                         $(self.$opt_field),*
                     };
-                    self.connection.request($method, self.get_path(), params, None)
+                    self.connection.send($method, self.get_path(), params, None, &self.request_options)
+                }
+
+                pub fn execute(&self) -> ::error::EsResult<String> {
+                    self.response().and_then(|r| r.into_body())
                 }
             }
     }
@@ -148,3 +216,46 @@ macro_rules! join_str {
         }
     }
 }
+
+#[macro_export]
+macro_rules! decodable_response {
+    ($($t:ty),*) => {
+        $(
+        impl FromResponse for $t {
+            fn from_json(json: Json) -> EsResult<$t> {
+                let mut decoder = json::Decoder::new(json);
+                Ok(try!(Decodable::decode(&mut decoder)))
+            }
+        }
+        )*
+    }
+}
+
+#[macro_export]
+macro_rules! impl_send {
+    ($c:ident => $r:ty) => {
+        impl<'a> $c<'a> {
+            pub fn send(&self) -> EsResult<$r> {
+                let body = try!(self.execute());
+                FromResponse::from_response(&body)
+            }
+        }
+    }
+}
+
+// Existence checks only look at the status: `true` for a 2xx response and
+// `false` for a 404.
+#[macro_export]
+macro_rules! impl_exists {
+    ($c:ident) => {
+        impl<'a> $c<'a> {
+            pub fn send(&self) -> ::error::EsResult<bool> {
+                let response = try!(self.response());
+                match response.status {
+                    404 => Ok(false),
+                    _ => response.check().map(|_| true)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,658 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use rustc_serialize::json::{self, Json, ToJson};
+
+//
+// Mapping DSL. A `Mapping` is the body of a single type's mapping, e.g. what
+// goes under `mappings.<type>` in a create-index request or is sent as the
+// body of put-mapping.
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dynamic {
+    True,
+    False,
+    Strict
+}
+
+impl ToString for Dynamic {
+    fn to_string(&self) -> String {
+        match *self {
+            Dynamic::True => "true".to_string(),
+            Dynamic::False => "false".to_string(),
+            Dynamic::Strict => "strict".to_string()
+        }
+    }
+}
+
+impl ToJson for Dynamic {
+    fn to_json(&self) -> Json {
+        match *self {
+            Dynamic::True => Json::Boolean(true),
+            Dynamic::False => Json::Boolean(false),
+            Dynamic::Strict => Json::String("strict".to_string())
+        }
+    }
+}
+
+impl Dynamic {
+    fn from_json(key: &str, json: &Json) -> Result<Dynamic, ParseMappingError> {
+        match *json {
+            Json::Boolean(true) => Ok(Dynamic::True),
+            Json::Boolean(false) => Ok(Dynamic::False),
+            Json::String(ref s) => match s.as_str() {
+                "true" => Ok(Dynamic::True),
+                "false" => Ok(Dynamic::False),
+                "strict" => Ok(Dynamic::Strict),
+                _ => Err(ParseMappingError::InvalidValue(key.to_string(), s.to_string()))
+            },
+            ref other => Err(ParseMappingError::InvalidValue(key.to_string(), other.to_string()))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldType {
+    Text,
+    Keyword,
+    Long,
+    Double,
+    Date,
+    Boolean,
+    GeoPoint,
+    Nested,
+    Object,
+    Ip,
+    Other(String)
+}
+
+impl ToString for FieldType {
+    fn to_string(&self) -> String {
+        match *self {
+            FieldType::Text => "text".to_string(),
+            FieldType::Keyword => "keyword".to_string(),
+            FieldType::Long => "long".to_string(),
+            FieldType::Double => "double".to_string(),
+            FieldType::Date => "date".to_string(),
+            FieldType::Boolean => "boolean".to_string(),
+            FieldType::GeoPoint => "geo_point".to_string(),
+            FieldType::Nested => "nested".to_string(),
+            FieldType::Object => "object".to_string(),
+            FieldType::Ip => "ip".to_string(),
+            FieldType::Other(ref s) => s.to_string()
+        }
+    }
+}
+
+impl<'a> From<&'a str> for FieldType {
+    fn from(s: &'a str) -> FieldType {
+        match s {
+            "text" => FieldType::Text,
+            "keyword" => FieldType::Keyword,
+            "long" => FieldType::Long,
+            "double" => FieldType::Double,
+            "date" => FieldType::Date,
+            "boolean" => FieldType::Boolean,
+            "geo_point" => FieldType::GeoPoint,
+            "nested" => FieldType::Nested,
+            "object" => FieldType::Object,
+            "ip" => FieldType::Ip,
+            other => FieldType::Other(other.to_string())
+        }
+    }
+}
+
+// A single field definition. `field_type` is only `None` for the typeless
+// mappings allowed inside dynamic templates. Keys this DSL does not model are
+// kept in `extra` so that a parsed mapping serializes back unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub field_type: Option<FieldType>,
+    pub analyzer: Option<String>,
+    pub search_analyzer: Option<String>,
+    pub format: Option<String>,
+    pub index: Option<bool>,
+    pub store: Option<bool>,
+    pub doc_values: Option<bool>,
+    pub null_value: Option<Json>,
+    pub dynamic: Option<Dynamic>,
+    pub fields: BTreeMap<String, Field>,
+    pub properties: BTreeMap<String, Field>,
+    pub extra: BTreeMap<String, Json>
+}
+
+impl Field {
+    pub fn new<T: Into<FieldType>>(field_type: T) -> Field {
+        let mut field = Field::untyped();
+        field.field_type = Some(field_type.into());
+        field
+    }
+
+    pub fn untyped() -> Field {
+        Field {
+            field_type: None,
+            analyzer: None,
+            search_analyzer: None,
+            format: None,
+            index: None,
+            store: None,
+            doc_values: None,
+            null_value: None,
+            dynamic: None,
+            fields: BTreeMap::new(),
+            properties: BTreeMap::new(),
+            extra: BTreeMap::new()
+        }
+    }
+
+    pub fn text() -> Field { Field::new(FieldType::Text) }
+    pub fn keyword() -> Field { Field::new(FieldType::Keyword) }
+    pub fn long() -> Field { Field::new(FieldType::Long) }
+    pub fn double() -> Field { Field::new(FieldType::Double) }
+    pub fn boolean() -> Field { Field::new(FieldType::Boolean) }
+    pub fn geo_point() -> Field { Field::new(FieldType::GeoPoint) }
+    pub fn nested() -> Field { Field::new(FieldType::Nested) }
+    pub fn object() -> Field { Field::new(FieldType::Object) }
+    pub fn ip() -> Field { Field::new(FieldType::Ip) }
+
+    pub fn date(format: Option<&str>) -> Field {
+        let mut field = Field::new(FieldType::Date);
+        field.format = format.map(|f| f.to_string());
+        field
+    }
+
+    pub fn analyzer(mut self, analyzer: &str) -> Field {
+        self.analyzer = Some(analyzer.to_string());
+        self
+    }
+
+    pub fn search_analyzer(mut self, analyzer: &str) -> Field {
+        self.search_analyzer = Some(analyzer.to_string());
+        self
+    }
+
+    pub fn format(mut self, format: &str) -> Field {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    pub fn index(mut self, index: bool) -> Field {
+        self.index = Some(index);
+        self
+    }
+
+    pub fn store(mut self, store: bool) -> Field {
+        self.store = Some(store);
+        self
+    }
+
+    pub fn doc_values(mut self, doc_values: bool) -> Field {
+        self.doc_values = Some(doc_values);
+        self
+    }
+
+    pub fn null_value<T: ToJson>(mut self, value: T) -> Field {
+        self.null_value = Some(value.to_json());
+        self
+    }
+
+    pub fn dynamic(mut self, dynamic: Dynamic) -> Field {
+        self.dynamic = Some(dynamic);
+        self
+    }
+
+    // multi-field, e.g. a `keyword` sub-field of a `text` field
+    pub fn field(mut self, name: &str, field: Field) -> Field {
+        self.fields.insert(name.to_string(), field);
+        self
+    }
+
+    // sub-property of an `object` or `nested` field
+    pub fn property(mut self, name: &str, field: Field) -> Field {
+        self.properties.insert(name.to_string(), field);
+        self
+    }
+
+    pub fn set<T: ToJson>(mut self, key: &str, value: T) -> Field {
+        self.extra.insert(key.to_string(), value.to_json());
+        self
+    }
+
+    pub fn from_json(json: &Json) -> Result<Field, ParseMappingError> {
+        let obj = try!(expect_object("field", json));
+        let mut field = Field::untyped();
+
+        for (key, value) in obj.iter() {
+            match key.as_str() {
+                "type" => field.field_type = Some(FieldType::from(try!(expect_string(key, value)))),
+                "analyzer" => field.analyzer = Some(try!(expect_string(key, value)).to_string()),
+                "search_analyzer" => field.search_analyzer = Some(try!(expect_string(key, value)).to_string()),
+                "format" => field.format = Some(try!(expect_string(key, value)).to_string()),
+                // pre-5.x values such as `"not_analyzed"` are kept verbatim
+                "index" => match expect_bool(key, value) {
+                    Ok(b) => field.index = Some(b),
+                    Err(_) => { field.extra.insert(key.to_string(), value.clone()); }
+                },
+                "store" => field.store = Some(try!(expect_bool(key, value))),
+                "doc_values" => field.doc_values = Some(try!(expect_bool(key, value))),
+                "null_value" => field.null_value = Some(value.clone()),
+                "dynamic" => field.dynamic = Some(try!(Dynamic::from_json(key, value))),
+                "fields" => field.fields = try!(parse_properties(key, value)),
+                "properties" => field.properties = try!(parse_properties(key, value)),
+                _ => { field.extra.insert(key.to_string(), value.clone()); }
+            }
+        }
+
+        // the cluster omits `"type": "object"` when returning object fields
+        if field.field_type.is_none() && !field.properties.is_empty() {
+            field.field_type = Some(FieldType::Object);
+        }
+
+        Ok(field)
+    }
+}
+
+impl ToJson for Field {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = self.extra.clone();
+
+        if let Some(ref t) = self.field_type { obj.insert("type".to_string(), t.to_string().to_json()); }
+        if let Some(ref a) = self.analyzer { obj.insert("analyzer".to_string(), a.to_json()); }
+        if let Some(ref a) = self.search_analyzer { obj.insert("search_analyzer".to_string(), a.to_json()); }
+        if let Some(ref f) = self.format { obj.insert("format".to_string(), f.to_json()); }
+        if let Some(i) = self.index { obj.insert("index".to_string(), i.to_json()); }
+        if let Some(s) = self.store { obj.insert("store".to_string(), s.to_json()); }
+        if let Some(d) = self.doc_values { obj.insert("doc_values".to_string(), d.to_json()); }
+        if let Some(ref n) = self.null_value { obj.insert("null_value".to_string(), n.clone()); }
+        if let Some(ref d) = self.dynamic { obj.insert("dynamic".to_string(), d.to_json()); }
+        if !self.fields.is_empty() { obj.insert("fields".to_string(), properties_to_json(&self.fields)); }
+        if !self.properties.is_empty() { obj.insert("properties".to_string(), properties_to_json(&self.properties)); }
+
+        Json::Object(obj)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceField {
+    pub enabled: Option<bool>,
+    pub includes: Vec<String>,
+    pub excludes: Vec<String>
+}
+
+impl SourceField {
+    pub fn enabled(enabled: bool) -> SourceField {
+        SourceField { enabled: Some(enabled), includes: Vec::new(), excludes: Vec::new() }
+    }
+
+    fn from_json(json: &Json) -> Result<SourceField, ParseMappingError> {
+        let obj = try!(expect_object("_source", json));
+        Ok(SourceField {
+            enabled: match obj.get("enabled") {
+                Some(e) => Some(try!(expect_bool("enabled", e))),
+                None => None
+            },
+            includes: try!(parse_string_list("includes", obj.get("includes"))),
+            excludes: try!(parse_string_list("excludes", obj.get("excludes")))
+        })
+    }
+}
+
+impl ToJson for SourceField {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = BTreeMap::new();
+        if let Some(e) = self.enabled { obj.insert("enabled".to_string(), e.to_json()); }
+        if !self.includes.is_empty() { obj.insert("includes".to_string(), self.includes.to_json()); }
+        if !self.excludes.is_empty() { obj.insert("excludes".to_string(), self.excludes.to_json()); }
+        Json::Object(obj)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllField {
+    pub enabled: Option<bool>,
+    pub analyzer: Option<String>
+}
+
+impl AllField {
+    pub fn enabled(enabled: bool) -> AllField {
+        AllField { enabled: Some(enabled), analyzer: None }
+    }
+
+    fn from_json(json: &Json) -> Result<AllField, ParseMappingError> {
+        let obj = try!(expect_object("_all", json));
+        Ok(AllField {
+            enabled: match obj.get("enabled") {
+                Some(e) => Some(try!(expect_bool("enabled", e))),
+                None => None
+            },
+            analyzer: match obj.get("analyzer") {
+                Some(a) => Some(try!(expect_string("analyzer", a)).to_string()),
+                None => None
+            }
+        })
+    }
+}
+
+impl ToJson for AllField {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = BTreeMap::new();
+        if let Some(e) = self.enabled { obj.insert("enabled".to_string(), e.to_json()); }
+        if let Some(ref a) = self.analyzer { obj.insert("analyzer".to_string(), a.to_json()); }
+        Json::Object(obj)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicTemplate {
+    pub name: String,
+    pub match_pattern: Option<String>,
+    pub unmatch: Option<String>,
+    pub path_match: Option<String>,
+    pub path_unmatch: Option<String>,
+    pub match_mapping_type: Option<String>,
+    pub mapping: Field
+}
+
+impl DynamicTemplate {
+    pub fn new(name: &str, mapping: Field) -> DynamicTemplate {
+        DynamicTemplate {
+            name: name.to_string(),
+            match_pattern: None,
+            unmatch: None,
+            path_match: None,
+            path_unmatch: None,
+            match_mapping_type: None,
+            mapping: mapping
+        }
+    }
+
+    pub fn match_pattern(mut self, pattern: &str) -> DynamicTemplate {
+        self.match_pattern = Some(pattern.to_string());
+        self
+    }
+
+    pub fn unmatch(mut self, pattern: &str) -> DynamicTemplate {
+        self.unmatch = Some(pattern.to_string());
+        self
+    }
+
+    pub fn path_match(mut self, pattern: &str) -> DynamicTemplate {
+        self.path_match = Some(pattern.to_string());
+        self
+    }
+
+    pub fn path_unmatch(mut self, pattern: &str) -> DynamicTemplate {
+        self.path_unmatch = Some(pattern.to_string());
+        self
+    }
+
+    pub fn match_mapping_type(mut self, mapping_type: &str) -> DynamicTemplate {
+        self.match_mapping_type = Some(mapping_type.to_string());
+        self
+    }
+
+    // dynamic templates are serialized as `{"<name>": {<template>}}`
+    fn from_json(json: &Json) -> Result<DynamicTemplate, ParseMappingError> {
+        let outer = try!(expect_object("dynamic_templates", json));
+        let (name, inner) = match outer.iter().next() {
+            Some((name, inner)) if outer.len() == 1 => (name, inner),
+            _ => return Err(ParseMappingError::InvalidValue("dynamic_templates".to_string(), json.to_string()))
+        };
+        let obj = try!(expect_object(name, inner));
+        let opt_string = |key: &str| -> Result<Option<String>, ParseMappingError> {
+            match obj.get(key) {
+                Some(v) => Ok(Some(try!(expect_string(key, v)).to_string())),
+                None => Ok(None)
+            }
+        };
+
+        Ok(DynamicTemplate {
+            name: name.to_string(),
+            match_pattern: try!(opt_string("match")),
+            unmatch: try!(opt_string("unmatch")),
+            path_match: try!(opt_string("path_match")),
+            path_unmatch: try!(opt_string("path_unmatch")),
+            match_mapping_type: try!(opt_string("match_mapping_type")),
+            mapping: match obj.get("mapping") {
+                Some(m) => try!(Field::from_json(m)),
+                None => return Err(ParseMappingError::MissingKey("mapping".to_string()))
+            }
+        })
+    }
+}
+
+impl ToJson for DynamicTemplate {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = BTreeMap::new();
+        if let Some(ref m) = self.match_pattern { obj.insert("match".to_string(), m.to_json()); }
+        if let Some(ref m) = self.unmatch { obj.insert("unmatch".to_string(), m.to_json()); }
+        if let Some(ref m) = self.path_match { obj.insert("path_match".to_string(), m.to_json()); }
+        if let Some(ref m) = self.path_unmatch { obj.insert("path_unmatch".to_string(), m.to_json()); }
+        if let Some(ref m) = self.match_mapping_type { obj.insert("match_mapping_type".to_string(), m.to_json()); }
+        obj.insert("mapping".to_string(), self.mapping.to_json());
+
+        let mut outer: json::Object = BTreeMap::new();
+        outer.insert(self.name.to_string(), Json::Object(obj));
+        Json::Object(outer)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    pub properties: BTreeMap<String, Field>,
+    pub dynamic: Option<Dynamic>,
+    pub source: Option<SourceField>,
+    pub all: Option<AllField>,
+    pub dynamic_templates: Vec<DynamicTemplate>,
+    pub extra: BTreeMap<String, Json>
+}
+
+impl Mapping {
+    pub fn new() -> Mapping {
+        Mapping {
+            properties: BTreeMap::new(),
+            dynamic: None,
+            source: None,
+            all: None,
+            dynamic_templates: Vec::new(),
+            extra: BTreeMap::new()
+        }
+    }
+
+    pub fn property(mut self, name: &str, field: Field) -> Mapping {
+        self.properties.insert(name.to_string(), field);
+        self
+    }
+
+    pub fn dynamic(mut self, dynamic: Dynamic) -> Mapping {
+        self.dynamic = Some(dynamic);
+        self
+    }
+
+    pub fn source(mut self, source: SourceField) -> Mapping {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn all(mut self, all: AllField) -> Mapping {
+        self.all = Some(all);
+        self
+    }
+
+    pub fn dynamic_template(mut self, template: DynamicTemplate) -> Mapping {
+        self.dynamic_templates.push(template);
+        self
+    }
+
+    pub fn set<T: ToJson>(mut self, key: &str, value: T) -> Mapping {
+        self.extra.insert(key.to_string(), value.to_json());
+        self
+    }
+
+    // `{"<type>": <mapping>}`, as expected under `mappings` when creating an index
+    pub fn for_type(&self, typ: &str) -> json::Object {
+        let mut obj: json::Object = BTreeMap::new();
+        obj.insert(typ.to_string(), self.to_json());
+        obj
+    }
+
+    pub fn from_json(json: &Json) -> Result<Mapping, ParseMappingError> {
+        let obj = try!(expect_object("mapping", json));
+        let mut mapping = Mapping::new();
+
+        for (key, value) in obj.iter() {
+            match key.as_str() {
+                "properties" => mapping.properties = try!(parse_properties(key, value)),
+                "dynamic" => mapping.dynamic = Some(try!(Dynamic::from_json(key, value))),
+                "_source" => mapping.source = Some(try!(SourceField::from_json(value))),
+                "_all" => mapping.all = Some(try!(AllField::from_json(value))),
+                "dynamic_templates" => {
+                    let templates = try!(expect_array(key, value));
+                    for t in templates.iter() {
+                        mapping.dynamic_templates.push(try!(DynamicTemplate::from_json(t)));
+                    }
+                },
+                _ => { mapping.extra.insert(key.to_string(), value.clone()); }
+            }
+        }
+
+        Ok(mapping)
+    }
+
+    // Parses the body returned by `GET /{index}/_mapping[/{type}]`, which is
+    // keyed by index and then by type.
+    pub fn from_response(json: &Json) -> Result<BTreeMap<String, BTreeMap<String, Mapping>>, ParseMappingError> {
+        let indices = try!(expect_object("response", json));
+        let mut out = BTreeMap::new();
+
+        for (index, value) in indices.iter() {
+            let index_obj = try!(expect_object(index, value));
+            let mut types = BTreeMap::new();
+            if let Some(mappings) = index_obj.get("mappings") {
+                for (typ, mapping) in try!(expect_object("mappings", mappings)).iter() {
+                    types.insert(typ.to_string(), try!(Mapping::from_json(mapping)));
+                }
+            }
+            out.insert(index.to_string(), types);
+        }
+
+        Ok(out)
+    }
+}
+
+impl ToJson for Mapping {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = self.extra.clone();
+
+        if !self.properties.is_empty() { obj.insert("properties".to_string(), properties_to_json(&self.properties)); }
+        if let Some(ref d) = self.dynamic { obj.insert("dynamic".to_string(), d.to_json()); }
+        if let Some(ref s) = self.source { obj.insert("_source".to_string(), s.to_json()); }
+        if let Some(ref a) = self.all { obj.insert("_all".to_string(), a.to_json()); }
+        if !self.dynamic_templates.is_empty() {
+            obj.insert("dynamic_templates".to_string(),
+                       Json::Array(self.dynamic_templates.iter().map(|t| t.to_json()).collect()));
+        }
+
+        Json::Object(obj)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseMappingError {
+    ExpectedObject(String),
+    MissingKey(String),
+    InvalidValue(String, String)
+}
+
+impl Error for ParseMappingError {
+    fn description(&self) -> &str {
+        match *self {
+            ParseMappingError::ExpectedObject(_) => "expected a JSON object",
+            ParseMappingError::MissingKey(_) => "missing required key",
+            ParseMappingError::InvalidValue(_, _) => "invalid value"
+        }
+    }
+}
+
+impl fmt::Display for ParseMappingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseMappingError::ExpectedObject(ref key) => write!(f, "expected `{}` to be an object", key),
+            ParseMappingError::MissingKey(ref key) => write!(f, "missing key `{}`", key),
+            ParseMappingError::InvalidValue(ref key, ref value) => write!(f, "invalid value for `{}`: {}", key, value)
+        }
+    }
+}
+
+fn properties_to_json(properties: &BTreeMap<String, Field>) -> Json {
+    Json::Object(properties.iter().map(|(k, v)| (k.to_string(), v.to_json())).collect())
+}
+
+fn parse_properties(key: &str, json: &Json) -> Result<BTreeMap<String, Field>, ParseMappingError> {
+    let mut out = BTreeMap::new();
+    for (name, value) in try!(expect_object(key, json)).iter() {
+        out.insert(name.to_string(), try!(Field::from_json(value)));
+    }
+    Ok(out)
+}
+
+fn parse_string_list(key: &str, json: Option<&Json>) -> Result<Vec<String>, ParseMappingError> {
+    match json {
+        None => Ok(Vec::new()),
+        Some(&Json::String(ref s)) => Ok(vec![s.to_string()]),
+        Some(value) => {
+            let mut out = Vec::new();
+            for v in try!(expect_array(key, value)).iter() {
+                out.push(try!(expect_string(key, v)).to_string());
+            }
+            Ok(out)
+        }
+    }
+}
+
+fn expect_object<'a>(key: &str, json: &'a Json) -> Result<&'a json::Object, ParseMappingError> {
+    json.as_object().ok_or(ParseMappingError::ExpectedObject(key.to_string()))
+}
+
+fn expect_array<'a>(key: &str, json: &'a Json) -> Result<&'a json::Array, ParseMappingError> {
+    json.as_array().ok_or(ParseMappingError::InvalidValue(key.to_string(), json.to_string()))
+}
+
+fn expect_string<'a>(key: &str, json: &'a Json) -> Result<&'a str, ParseMappingError> {
+    json.as_string().ok_or(ParseMappingError::InvalidValue(key.to_string(), json.to_string()))
+}
+
+// older clusters return booleans such as `index` and `store` as strings
+fn expect_bool(key: &str, json: &Json) -> Result<bool, ParseMappingError> {
+    match *json {
+        Json::Boolean(b) => Ok(b),
+        Json::String(ref s) if s == "true" => Ok(true),
+        Json::String(ref s) if s == "false" => Ok(false),
+        _ => Err(ParseMappingError::InvalidValue(key.to_string(), json.to_string()))
+    }
+}
+
+#[test]
+fn test_mapping_round_trip() {
+    let mapping = Mapping::new()
+        .dynamic(Dynamic::Strict)
+        .source(SourceField::enabled(true))
+        .all(AllField::enabled(false))
+        .property("title", Field::text().analyzer("english")
+                  .field("raw", Field::keyword()))
+        .property("created", Field::date(Some("yyyy-MM-dd")))
+        .property("location", Field::geo_point())
+        .property("author", Field::object()
+                  .property("name", Field::keyword())
+                  .property("ip", Field::ip()))
+        .dynamic_template(DynamicTemplate::new("strings", Field::keyword())
+                          .match_mapping_type("string"));
+
+    let parsed = Mapping::from_json(&mapping.to_json()).unwrap();
+    assert_eq!(mapping, parsed);
+
+    let from_cluster = Json::from_str(r#"{"properties": {"author": {"properties": {"name": {"type": "keyword"}}}}}"#).unwrap();
+    let parsed = Mapping::from_json(&from_cluster).unwrap();
+    assert_eq!(parsed.properties.get("author").unwrap().field_type, Some(FieldType::Object));
+}
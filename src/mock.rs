@@ -0,0 +1,404 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use rustc_serialize::json::{self, Json, ToJson};
+use url::Url;
+use hyper::method::Method;
+use error::{Error, EsResult};
+use transport::{Transport, TransportRequest, TransportResponse};
+use client::Client;
+
+//
+// Transports for testing request builders without a cluster:
+//
+// * `MockTransport` checks each request against a queue of expectations
+//   and answers with canned responses.
+// * `RecordingTransport` wraps a real transport and saves every exchange to
+//   a JSON fixture file, which `ReplayTransport` later answers from.
+//
+
+// e.g. "/logs/_search"
+fn request_path(request: &TransportRequest) -> String {
+    let mut path = String::new();
+    for segment in request.path.iter() {
+        path.push('/');
+        path.push_str(segment);
+    }
+    if path.is_empty() { path.push('/'); }
+    path
+}
+
+fn sorted(query: &[(String, String)]) -> Vec<(String, String)> {
+    let mut query = query.to_vec();
+    query.sort();
+    query
+}
+
+// Bodies are compared as JSON when both sides parse, so that key order and
+// whitespace don't matter. Bulk-style bodies are compared line by line.
+fn same_body(expected: &str, actual: &str) -> bool {
+    let expected_lines: Vec<&str> = expected.lines().filter(|l| !l.trim().is_empty()).collect();
+    let actual_lines: Vec<&str> = actual.lines().filter(|l| !l.trim().is_empty()).collect();
+    if expected_lines.len() != actual_lines.len() { return false; }
+
+    expected_lines.iter().zip(actual_lines.iter()).all(|(e, a)| {
+        match (Json::from_str(e), Json::from_str(a)) {
+            (Ok(e), Ok(a)) => e == a,
+            _ => e.trim() == a.trim()
+        }
+    })
+}
+
+fn body_string(request: &TransportRequest) -> Option<String> {
+    request.body.as_ref().map(|b| String::from_utf8_lossy(b).into_owned())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expectation {
+    method: Method,
+    path: String,
+    query: Option<Vec<(String, String)>>,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+    response: TransportResponse
+}
+
+impl Expectation {
+    // Expects a request to `path`, e.g. "/logs/_doc/1". Query and body are
+    // only checked when set; the default response is a `200` with `{}`.
+    pub fn new(method: Method, path: &str) -> Expectation {
+        Expectation {
+            method: method,
+            path: path.to_string(),
+            query: None,
+            headers: Vec::new(),
+            body: None,
+            response: TransportResponse { status: 200, headers: Vec::new(), body: "{}".to_string() }
+        }
+    }
+
+    // Adds an expected query pair. Once one pair is given, the query must
+    // match exactly, in any order.
+    pub fn query(mut self, name: &str, value: &str) -> Expectation {
+        let mut query = self.query.take().unwrap_or(Vec::new());
+        query.push((name.to_string(), value.to_string()));
+        self.query = Some(query);
+        self
+    }
+
+    pub fn no_query(mut self) -> Expectation {
+        self.query = Some(Vec::new());
+        self
+    }
+
+    // Expects a request header; other headers are not checked. Names are
+    // case-insensitive.
+    pub fn header(mut self, name: &str, value: &str) -> Expectation {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body<T: ToJson>(mut self, body: T) -> Expectation {
+        self.body = Some(body.to_json().to_string());
+        self
+    }
+
+    pub fn body_str(mut self, body: &str) -> Expectation {
+        self.body = Some(body.to_string());
+        self
+    }
+
+    pub fn respond(mut self, status: u16, body: &str) -> Expectation {
+        self.response.status = status;
+        self.response.body = body.to_string();
+        self
+    }
+
+    pub fn respond_header(mut self, name: &str, value: &str) -> Expectation {
+        self.response.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn check(&self, request: &TransportRequest) {
+        let path = request_path(request);
+        assert!(self.method == request.method && self.path == path,
+                "expected {} {}, got {} {}", self.method, self.path, request.method, path);
+
+        if let Some(ref query) = self.query {
+            assert!(sorted(query) == sorted(&request.query),
+                    "{} {}: expected query {:?}, got {:?}", self.method, self.path, query, request.query);
+        }
+
+        for &(ref name, ref value) in self.headers.iter() {
+            let actual = request.headers.iter().find(|&&(ref n, _)| n.to_lowercase() == name.to_lowercase());
+            assert!(actual.map(|&(_, ref v)| v == value).unwrap_or(false),
+                    "{} {}: expected header {}: {}, got {:?}", self.method, self.path, name, value, actual);
+        }
+
+        if let Some(ref expected) = self.body {
+            let actual = body_string(request).unwrap_or(String::new());
+            assert!(same_body(expected, &actual),
+                    "{} {}: expected body {}, got {}", self.method, self.path, expected, actual);
+        }
+    }
+}
+
+// Expectations are matched in order, and any mismatch or unexpected
+// request panics. Clones share the same queue, so the mock can be kept for
+// `verify` after handing a client to the code under test.
+#[derive(Debug, Clone)]
+pub struct MockTransport {
+    expectations: Arc<Mutex<VecDeque<Expectation>>>
+}
+
+impl MockTransport {
+    pub fn new() -> MockTransport {
+        MockTransport { expectations: Arc::new(Mutex::new(VecDeque::new())) }
+    }
+
+    pub fn expect(&self, expectation: Expectation) -> &MockTransport {
+        self.expectations.lock().unwrap().push_back(expectation);
+        self
+    }
+
+    // A client for `http://localhost:9200` whose requests go to this mock.
+    pub fn client(&self) -> Client {
+        Client::new_with_transport(Url::parse("http://localhost:9200").unwrap(), Arc::new(self.clone()))
+    }
+
+    // Panics unless every expected request was made.
+    pub fn verify(&self) {
+        let remaining = self.expectations.lock().unwrap();
+        assert!(remaining.is_empty(), "{} expected request(s) not made, next: {} {}",
+                remaining.len(), remaining[0].method, remaining[0].path);
+    }
+}
+
+impl Transport for MockTransport {
+    fn perform(&self, _: &Url, request: &TransportRequest) -> EsResult<TransportResponse> {
+        let next = self.expectations.lock().unwrap().pop_front();
+        match next {
+            Some(expectation) => {
+                expectation.check(request);
+                Ok(expectation.response)
+            },
+            None => panic!("unexpected request: {} {}", request.method, request_path(request))
+        }
+    }
+}
+
+//
+// Fixtures
+//
+
+// One recorded request and its response, as stored in fixture files.
+// Request headers, which may carry credentials, are not saved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exchange {
+    pub request: TransportRequest,
+    pub response: TransportResponse
+}
+
+fn pairs_to_json(pairs: &[(String, String)]) -> Json {
+    Json::Array(pairs.iter().map(|&(ref n, ref v)| Json::Array(vec![n.to_json(), v.to_json()])).collect())
+}
+
+fn pairs_from_json(json: Option<&Json>) -> EsResult<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    if let Some(list) = json.and_then(|j| j.as_array()) {
+        for pair in list.iter() {
+            match pair.as_array() {
+                Some(p) if p.len() == 2 && p[0].is_string() && p[1].is_string() => {
+                    pairs.push((p[0].as_string().unwrap().to_string(), p[1].as_string().unwrap().to_string()));
+                },
+                _ => return Err(Error::UnexpectedResponse(format!("invalid fixture pair: {}", pair)))
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+impl ToJson for Exchange {
+    fn to_json(&self) -> Json {
+        let mut request: json::Object = BTreeMap::new();
+        request.insert("method".to_string(), self.request.method.to_string().to_json());
+        request.insert("path".to_string(), self.request.path.to_json());
+        request.insert("query".to_string(), pairs_to_json(&self.request.query));
+        request.insert("body".to_string(), body_string(&self.request).to_json());
+
+        let mut response: json::Object = BTreeMap::new();
+        response.insert("status".to_string(), self.response.status.to_json());
+        response.insert("headers".to_string(), pairs_to_json(&self.response.headers));
+        response.insert("body".to_string(), self.response.body.to_json());
+
+        let mut obj: json::Object = BTreeMap::new();
+        obj.insert("request".to_string(), Json::Object(request));
+        obj.insert("response".to_string(), Json::Object(response));
+        Json::Object(obj)
+    }
+}
+
+impl Exchange {
+    pub fn from_json(json: &Json) -> EsResult<Exchange> {
+        let invalid = |what: &str| Error::UnexpectedResponse(format!("invalid fixture {}: {}", what, json));
+        let request = try!(json.find("request").ok_or(invalid("request")));
+        let response = try!(json.find("response").ok_or(invalid("response")));
+
+        let method = try!(request.find("method").and_then(|m| m.as_string())
+                          .and_then(|m| Method::from_str(m).ok()).ok_or(invalid("method")));
+        let mut path = Vec::new();
+        for segment in try!(request.find("path").and_then(|p| p.as_array()).ok_or(invalid("path"))).iter() {
+            path.push(try!(segment.as_string().ok_or(invalid("path"))).to_string());
+        }
+
+        let mut transport_request = TransportRequest::new(method, path);
+        transport_request.query = try!(pairs_from_json(request.find("query")));
+        transport_request.body = request.find("body").and_then(|b| b.as_string()).map(|b| b.as_bytes().to_vec());
+
+        Ok(Exchange {
+            request: transport_request,
+            response: TransportResponse {
+                status: try!(response.find("status").and_then(|s| s.as_u64()).ok_or(invalid("status"))) as u16,
+                headers: try!(pairs_from_json(response.find("headers"))),
+                body: try!(response.find("body").and_then(|b| b.as_string()).ok_or(invalid("body"))).to_string()
+            }
+        })
+    }
+
+    fn matches(&self, request: &TransportRequest) -> bool {
+        self.request.method == request.method
+            && self.request.path == request.path
+            && sorted(&self.request.query) == sorted(&request.query)
+            && match (body_string(&self.request), body_string(request)) {
+                (Some(e), Some(a)) => same_body(&e, &a),
+                (None, None) => true,
+                _ => false
+            }
+    }
+}
+
+pub fn save_fixture<P: AsRef<Path>>(path: P, exchanges: &[Exchange]) -> EsResult<()> {
+    let json = Json::Array(exchanges.iter().map(|e| e.to_json()).collect());
+    let mut file = try!(File::create(path));
+    try!(file.write_all(json.pretty().to_string().as_bytes()));
+    Ok(())
+}
+
+pub fn load_fixture<P: AsRef<Path>>(path: P) -> EsResult<Vec<Exchange>> {
+    let mut text = String::new();
+    try!(try!(File::open(path)).read_to_string(&mut text));
+    let json = try!(Json::from_str(&text));
+
+    let mut exchanges = Vec::new();
+    for exchange in try!(json.as_array().ok_or(Error::UnexpectedResponse(
+        "fixture must be an array of exchanges".to_string()))).iter() {
+        exchanges.push(try!(Exchange::from_json(exchange)));
+    }
+    Ok(exchanges)
+}
+
+// Performs requests through `inner` and keeps each exchange. `save` writes
+// them to the fixture file given to `new`.
+#[derive(Clone)]
+pub struct RecordingTransport {
+    inner: Arc<Transport>,
+    fixture: PathBuf,
+    exchanges: Arc<Mutex<Vec<Exchange>>>
+}
+
+impl RecordingTransport {
+    pub fn new<P: AsRef<Path>>(inner: Arc<Transport>, fixture: P) -> RecordingTransport {
+        RecordingTransport {
+            inner: inner,
+            fixture: fixture.as_ref().to_path_buf(),
+            exchanges: Arc::new(Mutex::new(Vec::new()))
+        }
+    }
+
+    pub fn exchanges(&self) -> Vec<Exchange> {
+        self.exchanges.lock().unwrap().clone()
+    }
+
+    pub fn save(&self) -> EsResult<()> {
+        save_fixture(&self.fixture, &self.exchanges())
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn perform(&self, host: &Url, request: &TransportRequest) -> EsResult<TransportResponse> {
+        let response = try!(self.inner.perform(host, request));
+        self.exchanges.lock().unwrap().push(Exchange { request: request.clone(), response: response.clone() });
+        Ok(response)
+    }
+}
+
+// Answers requests from recorded exchanges. Each request is matched to the
+// first unused exchange with the same method, path, query and body, so
+// repeated requests replay their responses in recorded order.
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    exchanges: Arc<Mutex<Vec<Exchange>>>
+}
+
+impl ReplayTransport {
+    pub fn new(exchanges: Vec<Exchange>) -> ReplayTransport {
+        ReplayTransport { exchanges: Arc::new(Mutex::new(exchanges)) }
+    }
+
+    pub fn load<P: AsRef<Path>>(fixture: P) -> EsResult<ReplayTransport> {
+        Ok(ReplayTransport::new(try!(load_fixture(fixture))))
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn perform(&self, _: &Url, request: &TransportRequest) -> EsResult<TransportResponse> {
+        let mut exchanges = self.exchanges.lock().unwrap();
+        match exchanges.iter().position(|e| e.matches(request)) {
+            Some(i) => Ok(exchanges.remove(i).response),
+            None => Err(Error::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no recorded exchange for {} {}", request.method, request_path(request)))))
+        }
+    }
+}
+
+#[test]
+fn test_mock_transport() {
+    let mock = MockTransport::new();
+    mock.expect(Expectation::new(Method::Get, "/logs/event/1")
+                .no_query()
+                .respond(200, r#"{"_index": "logs", "_type": "event", "_id": "1", "found": true}"#));
+    mock.expect(Expectation::new(Method::Post, "/logs/event/1/_update")
+                .query("refresh", "true")
+                .body_str(r#"{"script": {"source": "ctx._source.n += 1"}}"#));
+
+    let client = mock.client();
+    let body = client.get("logs", "event", "1").execute().unwrap();
+    assert!(body.contains("\"found\": true"));
+
+    let mut update: json::Object = BTreeMap::new();
+    update.insert("script".to_string(), Json::from_str(r#"{"source": "ctx._source.n += 1"}"#).unwrap());
+    client.update("logs", "event", "1", update).refresh(true).execute().unwrap();
+
+    mock.verify();
+}
+
+#[test]
+fn test_fixture_round_trip() {
+    let mut request = TransportRequest::new(Method::Put, vec!["logs".to_string()]);
+    request.query.push(("wait_for_active_shards".to_string(), "1".to_string()));
+    request.body = Some(br#"{"settings": {}}"#.to_vec());
+    let exchange = Exchange {
+        request: request.clone(),
+        response: TransportResponse { status: 200, headers: Vec::new(), body: r#"{"acknowledged": true}"#.to_string() }
+    };
+    assert_eq!(Exchange::from_json(&exchange.to_json()).unwrap(), exchange);
+
+    let replay = ReplayTransport::new(vec![exchange]);
+    let host = Url::parse("http://localhost:9200").unwrap();
+    assert_eq!(replay.perform(&host, &request).unwrap().body, r#"{"acknowledged": true}"#);
+    assert!(replay.perform(&host, &request).is_err());
+}
@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use rustc_serialize::Decodable;
+use rustc_serialize::json::{self, Json};
+use hyper::method::Method::Get;
+use connection::Connection;
+use error::{Error, EsResult};
+use response::{FromResponse, find_string};
+use types::*;
+
+// A node selector, as accepted wherever a `node_id` is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeFilter {
+    All,
+    Local,
+    Master,
+    Id(String),
+    Name(String),
+    // e.g. `Role("data", false)` for `data:false`
+    Role(String, bool),
+    // e.g. `Attribute("rack", "r1")` for `rack:r1`
+    Attribute(String, String)
+}
+
+impl fmt::Display for NodeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NodeFilter::All => write!(f, "_all"),
+            NodeFilter::Local => write!(f, "_local"),
+            NodeFilter::Master => write!(f, "_master"),
+            NodeFilter::Id(ref id) => write!(f, "{}", id),
+            NodeFilter::Name(ref name) => write!(f, "{}", name),
+            NodeFilter::Role(ref role, enabled) => write!(f, "{}:{}", role, enabled),
+            NodeFilter::Attribute(ref name, ref value) => write!(f, "{}:{}", name, value)
+        }
+    }
+}
+
+pub fn node_filters(filters: &[NodeFilter]) -> StringList {
+    StringList(filters.iter().map(|f| f.to_string()).collect())
+}
+
+new_query_struct!{ InfoRequest(node_id: Option<StringList>, metric: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_nodes".to_string()];
+        if let Some(ref node_id) = self.node_id { path.push(node_id.to_string()); }
+        if let Some(ref metric) = self.metric { path.push(metric.to_string()); }
+        path
+    },
+    query_params => [
+        (flat_settings: FlatSettings, None),
+        (timeout: Timeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ InfoRequest => NodesInfo }
+
+new_query_struct!{ StatsRequest(node_id: Option<StringList>, metric: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_nodes".to_string()];
+        if let Some(ref node_id) = self.node_id { path.push(node_id.to_string()); }
+        path.push("stats".to_string());
+        if let Some(ref metric) = self.metric { path.push(metric.to_string()); }
+        path
+    },
+    query_params => [
+        (level: Level, None),
+        (fields: Fields, None),
+        (timeout: Timeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ StatsRequest => NodesStats }
+
+// The response is plain text, so only `execute` is available.
+new_query_struct!{ HotThreadsRequest(node_id: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_nodes".to_string()];
+        if let Some(ref node_id) = self.node_id { path.push(node_id.to_string()); }
+        path.push("hot_threads".to_string());
+        path
+    },
+    query_params => [
+        (threads: Threads, None),
+        (interval: Interval, None),
+        (snapshots: Snapshots, None),
+        (thread_type: HotThreadsType, None),
+        (ignore_idle_threads: IgnoreIdleThreads, None),
+        (timeout: Timeout, None)
+    ],
+    method => Get
+}}
+
+//
+// _nodes
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeInfo {
+    pub name: String,
+    pub transport_address: Option<String>,
+    pub host: Option<String>,
+    pub ip: Option<String>,
+    pub version: Option<String>,
+    pub roles: Vec<String>,
+    pub attributes: BTreeMap<String, String>,
+    // settings, os, jvm, plugins and whichever other metrics were requested
+    pub raw: Json
+}
+
+impl NodeInfo {
+    fn from_json(json: &Json) -> EsResult<NodeInfo> {
+        let opt_string = |key: &str| json.find(key).and_then(|v| v.as_string()).map(|v| v.to_string());
+
+        let mut roles = Vec::new();
+        if let Some(list) = json.find("roles").and_then(|r| r.as_array()) {
+            for role in list.iter() {
+                if let Some(r) = role.as_string() { roles.push(r.to_string()); }
+            }
+        }
+
+        let mut attributes = BTreeMap::new();
+        if let Some(obj) = json.find("attributes").and_then(|a| a.as_object()) {
+            for (k, v) in obj.iter() {
+                if let Some(v) = v.as_string() { attributes.insert(k.to_string(), v.to_string()); }
+            }
+        }
+
+        Ok(NodeInfo {
+            name: try!(opt_string("name").ok_or(Error::UnexpectedResponse(format!("node without a name: {}", json)))),
+            transport_address: opt_string("transport_address"),
+            host: opt_string("host"),
+            ip: opt_string("ip"),
+            version: opt_string("version"),
+            roles: roles,
+            attributes: attributes,
+            raw: json.clone()
+        })
+    }
+}
+
+// `nodes` is keyed by node id
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodesInfo {
+    pub cluster_name: String,
+    pub nodes: BTreeMap<String, NodeInfo>
+}
+
+impl FromResponse for NodesInfo {
+    fn from_json(json: Json) -> EsResult<NodesInfo> {
+        let cluster_name = try!(find_string(&json, "cluster_name"));
+        let mut nodes = BTreeMap::new();
+        if let Some(obj) = json.find("nodes").and_then(|n| n.as_object()) {
+            for (id, node) in obj.iter() {
+                nodes.insert(id.to_string(), try!(NodeInfo::from_json(node)));
+            }
+        }
+        Ok(NodesInfo { cluster_name: cluster_name.to_string(), nodes: nodes })
+    }
+}
+
+//
+// _nodes/stats
+//
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct JvmMemStats {
+    pub heap_used_in_bytes: u64,
+    pub heap_used_percent: u64,
+    pub heap_committed_in_bytes: u64,
+    pub heap_max_in_bytes: u64,
+    pub non_heap_used_in_bytes: Option<u64>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct JvmStats {
+    pub timestamp: u64,
+    pub uptime_in_millis: u64,
+    pub mem: JvmMemStats
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct OsCpuStats {
+    pub percent: i64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct OsMemStats {
+    pub total_in_bytes: u64,
+    pub free_in_bytes: u64,
+    pub used_in_bytes: u64,
+    pub free_percent: u64,
+    pub used_percent: u64
+}
+
+// older clusters report `cpu_percent` instead of `cpu.percent`
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct OsStats {
+    pub timestamp: u64,
+    pub cpu: Option<OsCpuStats>,
+    pub cpu_percent: Option<i64>,
+    pub mem: Option<OsMemStats>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct ProcessCpuStats {
+    pub percent: i64,
+    pub total_in_millis: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct ProcessStats {
+    pub timestamp: u64,
+    pub open_file_descriptors: i64,
+    pub max_file_descriptors: Option<i64>,
+    pub cpu: Option<ProcessCpuStats>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct ThreadPoolStats {
+    pub threads: u64,
+    pub queue: u64,
+    pub active: u64,
+    pub rejected: u64,
+    pub largest: u64,
+    pub completed: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct FsTotalStats {
+    pub total_in_bytes: u64,
+    pub free_in_bytes: u64,
+    pub available_in_bytes: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct FsStats {
+    pub timestamp: u64,
+    pub total: FsTotalStats
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct BreakerStats {
+    pub limit_size_in_bytes: i64,
+    pub estimated_size_in_bytes: i64,
+    pub overhead: f64,
+    pub tripped: u64
+}
+
+// Sections are `None` when excluded by the requested metrics.
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct NodeStats {
+    pub name: String,
+    pub transport_address: Option<String>,
+    pub host: Option<String>,
+    pub jvm: Option<JvmStats>,
+    pub os: Option<OsStats>,
+    pub process: Option<ProcessStats>,
+    pub thread_pool: Option<BTreeMap<String, ThreadPoolStats>>,
+    pub fs: Option<FsStats>,
+    pub breakers: Option<BTreeMap<String, BreakerStats>>
+}
+
+// `nodes` is keyed by node id
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable)]
+pub struct NodesStats {
+    pub cluster_name: String,
+    pub nodes: BTreeMap<String, NodeStats>
+}
+
+decodable_response!(NodesStats);
+
+#[test]
+fn test_node_filters() {
+    let filters = node_filters(&[NodeFilter::Local,
+                                 NodeFilter::Role("data".to_string(), false),
+                                 NodeFilter::Attribute("rack".to_string(), "r1".to_string())]);
+    assert_eq!(filters.to_string(), "_local,data:false,rack:r1");
+}
+
+#[test]
+fn test_nodes_stats_response() {
+    let body = r#"{"_nodes": {"total": 2, "successful": 2, "failed": 0}, "cluster_name": "logs-prod", "nodes": {
+        "n1": {"timestamp": 1500000000000, "name": "es-1", "transport_address": "10.0.0.1:9300", "host": "10.0.0.1",
+               "roles": ["master", "data", "ingest"],
+               "jvm": {"timestamp": 1500000000000, "uptime_in_millis": 3600000,
+                       "mem": {"heap_used_in_bytes": 536870912, "heap_used_percent": 25,
+                               "heap_committed_in_bytes": 2147483648, "heap_max_in_bytes": 2147483648,
+                               "non_heap_used_in_bytes": 104857600, "pools": {}}},
+               "os": {"timestamp": 1500000000000, "cpu": {"percent": 7, "load_average": {"1m": 0.5}},
+                      "mem": {"total_in_bytes": 8589934592, "free_in_bytes": 1073741824, "used_in_bytes": 7516192768,
+                              "free_percent": 12, "used_percent": 88}},
+               "process": {"timestamp": 1500000000000, "open_file_descriptors": 310, "max_file_descriptors": 65536,
+                           "cpu": {"percent": 3, "total_in_millis": 120000}},
+               "thread_pool": {"search": {"threads": 7, "queue": 0, "active": 1, "rejected": 12,
+                                          "largest": 7, "completed": 4000},
+                               "write": {"threads": 4, "queue": 2, "active": 4, "rejected": 0,
+                                         "largest": 4, "completed": 9000}},
+               "fs": {"timestamp": 1500000000000, "total": {"total_in_bytes": 107374182400,
+                                                             "free_in_bytes": 53687091200,
+                                                             "available_in_bytes": 48318382080}, "data": []},
+               "breakers": {"parent": {"limit_size_in_bytes": 1503238553, "limit_size": "1.3gb",
+                                       "estimated_size_in_bytes": 536870912, "estimated_size": "512mb",
+                                       "overhead": 1.0, "tripped": 0},
+                            "fielddata": {"limit_size_in_bytes": 858993459, "estimated_size_in_bytes": 0,
+                                          "overhead": 1.03, "tripped": 2}}},
+        "n2": {"timestamp": 1500000000000, "name": "es-2",
+               "os": {"timestamp": 1500000000000, "cpu_percent": 41}}}}"#;
+    let stats = NodesStats::from_response(body).unwrap();
+    assert_eq!(stats.cluster_name, "logs-prod");
+
+    let n1 = stats.nodes.get("n1").unwrap();
+    assert_eq!(n1.name, "es-1");
+    assert_eq!(n1.host, Some("10.0.0.1".to_string()));
+    let jvm = n1.jvm.as_ref().unwrap();
+    assert_eq!(jvm.mem.heap_used_percent, 25);
+    assert_eq!(jvm.mem.heap_max_in_bytes, 2147483648);
+    let os = n1.os.as_ref().unwrap();
+    assert_eq!(os.cpu, Some(OsCpuStats { percent: 7 }));
+    assert_eq!(os.cpu_percent, None);
+    assert_eq!(os.mem.as_ref().unwrap().used_percent, 88);
+    let process = n1.process.as_ref().unwrap();
+    assert_eq!(process.max_file_descriptors, Some(65536));
+    assert_eq!(process.cpu, Some(ProcessCpuStats { percent: 3, total_in_millis: 120000 }));
+    let thread_pool = n1.thread_pool.as_ref().unwrap();
+    assert_eq!(thread_pool.get("search").unwrap().rejected, 12);
+    assert_eq!(thread_pool.get("write").unwrap().queue, 2);
+    assert_eq!(n1.fs.as_ref().unwrap().total.available_in_bytes, 48318382080);
+    let breakers = n1.breakers.as_ref().unwrap();
+    assert_eq!(breakers.get("fielddata").unwrap().tripped, 2);
+    assert_eq!(breakers.get("parent").unwrap().limit_size_in_bytes, 1503238553);
+
+    // an older node, and sections that weren't requested
+    let n2 = stats.nodes.get("n2").unwrap();
+    let os = n2.os.as_ref().unwrap();
+    assert_eq!(os.cpu, None);
+    assert_eq!(os.cpu_percent, Some(41));
+    assert_eq!(os.mem, None);
+    assert_eq!(n2.transport_address, None);
+    assert!(n2.jvm.is_none() && n2.process.is_none() && n2.thread_pool.is_none());
+    assert!(n2.fs.is_none() && n2.breakers.is_none());
+}
+
+#[test]
+fn test_nodes_info_response() {
+    let body = r#"{"_nodes": {"total": 2, "successful": 2, "failed": 0}, "cluster_name": "logs-prod", "nodes": {
+        "n1": {"name": "es-1", "transport_address": "10.0.0.1:9300", "host": "10.0.0.1", "ip": "10.0.0.1",
+               "version": "6.8.0", "build_hash": "65b6179", "roles": ["master", "data"],
+               "attributes": {"rack": "r1", "ml.enabled": "true"},
+               "jvm": {"version": "1.8.0_212", "mem": {"heap_max_in_bytes": 2147483648}}},
+        "n2": {"name": "es-2"}}}"#;
+    let info = NodesInfo::from_response(body).unwrap();
+    assert_eq!(info.cluster_name, "logs-prod");
+
+    let n1 = info.nodes.get("n1").unwrap();
+    assert_eq!(n1.version, Some("6.8.0".to_string()));
+    assert_eq!(n1.ip, Some("10.0.0.1".to_string()));
+    assert_eq!(n1.roles, vec!["master".to_string(), "data".to_string()]);
+    assert_eq!(n1.attributes.get("rack"), Some(&"r1".to_string()));
+    assert_eq!(n1.raw.find_path(&["jvm", "version"]), Some(&Json::String("1.8.0_212".to_string())));
+
+    let n2 = info.nodes.get("n2").unwrap();
+    assert_eq!(n2.transport_address, None);
+    assert!(n2.roles.is_empty() && n2.attributes.is_empty());
+
+    assert!(NodesInfo::from_response(r#"{"cluster_name": "logs-prod", "nodes": {"n1": {}}}"#).is_err());
+    assert!(NodesInfo::from_response(r#"{"nodes": {}}"#).is_err());
+}
@@ -0,0 +1,131 @@
+use rustc_serialize::Decodable;
+use rustc_serialize::json::{self, Json};
+use error::{Error, EsResult};
+
+pub trait FromResponse: Sized {
+    fn from_json(json: Json) -> EsResult<Self>;
+
+    fn from_response(body: &str) -> EsResult<Self> {
+        let json = try!(Json::from_str(body));
+        if json.find("error").is_some() && json.find("status").is_some() {
+            return Err(Error::Elasticsearch(json));
+        }
+        Self::from_json(json)
+    }
+}
+
+// for endpoints whose responses are left untyped
+impl FromResponse for Json {
+    fn from_json(json: Json) -> EsResult<Json> {
+        Ok(json)
+    }
+}
+
+// Decodes a sub-tree of a response whose top level needs manual handling.
+pub fn decode_json<T: Decodable>(json: Json) -> EsResult<T> {
+    let mut decoder = json::Decoder::new(json);
+    Ok(try!(Decodable::decode(&mut decoder)))
+}
+
+// A field a response can't be decoded without.
+pub fn find_field<'a>(json: &'a Json, key: &str) -> EsResult<&'a Json> {
+    json.find(key).ok_or(Error::UnexpectedResponse(format!("missing `{}`", key)))
+}
+
+pub fn find_string(json: &Json, key: &str) -> EsResult<String> {
+    find_field(json, key).and_then(|v| v.as_string().map(|s| s.to_string())
+                                   .ok_or(Error::UnexpectedResponse(format!("expected `{}` to be a string", key))))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct AcknowledgedResponse {
+    pub acknowledged: bool
+}
+
+decodable_response!(AcknowledgedResponse);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardFailure {
+    pub index: Option<String>,
+    pub shard: Option<u64>,
+    pub node: Option<String>,
+    pub status: Option<String>,
+    // a plain string on older clusters, an object with `type`/`reason` on newer ones
+    pub reason: Json
+}
+
+impl ShardFailure {
+    pub fn from_json(json: &Json) -> ShardFailure {
+        ShardFailure {
+            index: json.find("index").and_then(|i| i.as_string()).map(|i| i.to_string()),
+            shard: json.find("shard").and_then(|s| s.as_u64()),
+            node: json.find("node").and_then(|n| n.as_string()).map(|n| n.to_string()),
+            status: json.find("status").and_then(|s| s.as_string()).map(|s| s.to_string()),
+            reason: json.find("reason").map(|r| r.clone()).unwrap_or(Json::Null)
+        }
+    }
+}
+
+// The `_shards` summary returned by broadcast operations such as refresh,
+// flush and force-merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardStatistics {
+    pub total: u64,
+    pub successful: u64,
+    pub failed: u64,
+    pub failures: Vec<ShardFailure>
+}
+
+impl ShardStatistics {
+    pub fn from_json(json: &Json) -> EsResult<ShardStatistics> {
+        let count = |key: &str| -> EsResult<u64> {
+            json.find(key).and_then(|c| c.as_u64())
+                .ok_or(Error::UnexpectedResponse(format!("_shards.{} missing from {}", key, json)))
+        };
+
+        Ok(ShardStatistics {
+            total: try!(count("total")),
+            successful: try!(count("successful")),
+            failed: try!(count("failed")),
+            failures: match json.find("failures").and_then(|f| f.as_array()) {
+                Some(failures) => failures.iter().map(ShardFailure::from_json).collect(),
+                None => Vec::new()
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardsResponse {
+    pub shards: ShardStatistics
+}
+
+impl FromResponse for ShardsResponse {
+    fn from_json(json: Json) -> EsResult<ShardsResponse> {
+        match json.find("_shards") {
+            Some(shards) => Ok(ShardsResponse { shards: try!(ShardStatistics::from_json(shards)) }),
+            None => Err(Error::UnexpectedResponse(json.to_string()))
+        }
+    }
+}
+
+#[test]
+fn test_shards_response() {
+    let response = ShardsResponse::from_response(r#"{"_shards": {"total": 10, "successful": 10, "failed": 0}}"#).unwrap();
+    assert_eq!(response.shards, ShardStatistics { total: 10, successful: 10, failed: 0, failures: Vec::new() });
+
+    let body = r#"{"_shards": {"total": 2, "successful": 1, "failed": 1, "failures": [
+        {"index": "logs", "shard": 0, "status": "INTERNAL_SERVER_ERROR",
+         "reason": {"type": "flush_failed_engine_exception", "reason": "flush failed"}}]}}"#;
+    let response = ShardsResponse::from_response(body).unwrap();
+    assert_eq!(response.shards.failed, 1);
+    let failure = &response.shards.failures[0];
+    assert_eq!(failure.index, Some("logs".to_string()));
+    assert_eq!(failure.shard, Some(0));
+    assert_eq!(failure.node, None);
+    assert_eq!(failure.reason.find("type").and_then(|t| t.as_string()), Some("flush_failed_engine_exception"));
+
+    assert!(ShardsResponse::from_response(r#"{"acknowledged": true}"#).is_err());
+    assert!(ShardsResponse::from_response(r#"{"_shards": {"total": 2}}"#).is_err());
+}
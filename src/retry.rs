@@ -0,0 +1,183 @@
+use std::io;
+use chrono::Duration;
+use time::precise_time_ns;
+use hyper::method::Method;
+use hyper::method::Method::{Get, Head, Put, Delete, Options};
+use error::Error;
+
+//
+// Retries of failed requests. A request is retried when it failed with a
+// retryable error or status, has an idempotent method (or opted in with
+// `retry_non_idempotent`), and neither the retry count nor the total time
+// budget is used up.
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    // the first backoff; each retry doubles it, up to `max_backoff`
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    // time since the first attempt after which no retry is started
+    pub max_elapsed: Option<Duration>,
+    // e.g. 429, 502, 503 and 504
+    pub retry_statuses: Vec<u16>,
+    pub retry_timeouts: bool,
+    // refused, reset or aborted connections
+    pub retry_connection_errors: bool,
+    // retry POSTs too, for every request
+    pub retry_non_idempotent: bool
+}
+
+impl RetryPolicy {
+    pub fn new() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::milliseconds(100),
+            max_backoff: Duration::seconds(10),
+            max_elapsed: Some(Duration::seconds(30)),
+            retry_statuses: vec![429, 502, 503, 504],
+            retry_timeouts: true,
+            retry_connection_errors: true,
+            retry_non_idempotent: false
+        }
+    }
+
+    // Never retries; the default for connections.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy { max_retries: 0, .. RetryPolicy::new() }
+    }
+
+    pub fn max_retries(mut self, max: u32) -> RetryPolicy {
+        self.max_retries = max;
+        self
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> RetryPolicy {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn max_elapsed(mut self, max: Option<Duration>) -> RetryPolicy {
+        self.max_elapsed = max;
+        self
+    }
+
+    pub fn retry_statuses(mut self, statuses: Vec<u16>) -> RetryPolicy {
+        self.retry_statuses = statuses;
+        self
+    }
+
+    pub fn retry_timeouts(mut self, retry: bool) -> RetryPolicy {
+        self.retry_timeouts = retry;
+        self
+    }
+
+    pub fn retry_connection_errors(mut self, retry: bool) -> RetryPolicy {
+        self.retry_connection_errors = retry;
+        self
+    }
+
+    pub fn retry_non_idempotent(mut self, retry: bool) -> RetryPolicy {
+        self.retry_non_idempotent = retry;
+        self
+    }
+
+    pub fn retries_status(&self, status: u16) -> bool {
+        self.retry_statuses.contains(&status)
+    }
+
+    pub fn retries_error(&self, err: &Error) -> bool {
+        match *err {
+            Error::Timeout(_) => self.retry_timeouts,
+            Error::Io(ref e) => self.retry_connection_errors && match e.kind() {
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::NotConnected => true,
+                _ => false
+            },
+            _ => false
+        }
+    }
+
+    pub fn retries_method(&self, method: &Method, opted_in: bool) -> bool {
+        opted_in || self.retry_non_idempotent || is_idempotent(method)
+    }
+
+    // The wait before retry number `retry` (from 0): half of the capped
+    // exponential backoff, plus a random part of the other half, so that
+    // clients failing together don't retry in lockstep.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let initial = self.initial_backoff.num_milliseconds() as u64;
+        let max = self.max_backoff.num_milliseconds() as u64;
+        let backoff = ::std::cmp::min(initial.saturating_mul(1u64 << ::std::cmp::min(retry, 32)), max);
+        Duration::milliseconds((backoff / 2 + jitter(backoff - backoff / 2)) as i64)
+    }
+}
+
+pub fn is_idempotent(method: &Method) -> bool {
+    match *method {
+        Get | Head | Put | Delete | Options => true,
+        _ => false
+    }
+}
+
+// A value in [0, bound), from a xorshift of the clock.
+fn jitter(bound: u64) -> u64 {
+    if bound == 0 { return 0; }
+    let mut x = precise_time_ns() ^ 0x9E3779B97F4A7C15;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    x % bound
+}
+
+#[test]
+fn test_delay() {
+    let policy = RetryPolicy::new().backoff(Duration::milliseconds(100), Duration::seconds(1));
+    for retry in 0..10 {
+        let backoff = ::std::cmp::min(100 << retry, 1000);
+        let delay = policy.delay(retry).num_milliseconds();
+        assert!(delay >= backoff / 2 && delay < backoff, "retry {}: {}ms", retry, delay);
+    }
+}
+
+#[test]
+fn test_retries_idempotent_requests() {
+    use std::collections::BTreeMap;
+    use rustc_serialize::json::{self, Json};
+    use hyper::method::Method::Post;
+    use mock::{MockTransport, Expectation};
+
+    let mock = MockTransport::new();
+    mock.expect(Expectation::new(Get, "/logs/event/1").respond(503, "{}"));
+    mock.expect(Expectation::new(Get, "/logs/event/1").respond(200, r#"{"found": true}"#));
+    mock.expect(Expectation::new(Post, "/logs/event").respond(503, "{}"));
+    mock.expect(Expectation::new(Put, "/logs/event/2").respond(503, "{}"));
+    mock.expect(Expectation::new(Put, "/logs/event/2").respond(201, r#"{"_version": 1}"#));
+    for _ in 0..2 {
+        mock.expect(Expectation::new(Delete, "/logs/event/3").respond(503, "{}"));
+    }
+
+    let policy = RetryPolicy::new().max_retries(1).backoff(Duration::milliseconds(1), Duration::milliseconds(1));
+    let client = mock.client()
+        .with_retry_policy(policy);
+
+    assert_eq!(client.get("logs", "event", "1").execute().unwrap(), r#"{"found": true}"#);
+
+    let mut source: json::Object = BTreeMap::new();
+    source.insert("n".to_string(), Json::U64(1));
+    assert_eq!(client.index("logs", "event", None, source.clone()).execute().unwrap_err().status(), Some(503));
+
+    // indexing with an id is a PUT, so it is retried
+    assert_eq!(client.index("logs", "event", Some("2".to_string()), source).execute().unwrap(), r#"{"_version": 1}"#);
+
+    // once retries run out, a retryable status is an error even on `send`
+    let options = ::transport::RequestOptions::new();
+    let path = vec!["logs".to_string(), "event".to_string(), "3".to_string()];
+    assert_eq!(client.connection.send(Delete, path, Vec::new(), None, &options).unwrap_err().status(), Some(503));
+
+    mock.verify();
+}
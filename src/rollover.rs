@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+use rustc_serialize::Decodable;
+use rustc_serialize::json::{self, Json, ToJson};
+use config::IndexConfig;
+use error::EsResult;
+use response::FromResponse;
+use types::Timeout;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RolloverConditions {
+    pub max_age: Option<Timeout>,
+    pub max_docs: Option<u64>,
+    // a byte size value such as "50gb"
+    pub max_size: Option<String>
+}
+
+impl RolloverConditions {
+    pub fn new() -> RolloverConditions {
+        RolloverConditions { max_age: None, max_docs: None, max_size: None }
+    }
+
+    pub fn max_age(mut self, max_age: Timeout) -> RolloverConditions {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn max_docs(mut self, max_docs: u64) -> RolloverConditions {
+        self.max_docs = Some(max_docs);
+        self
+    }
+
+    pub fn max_size(mut self, max_size: &str) -> RolloverConditions {
+        self.max_size = Some(max_size.to_string());
+        self
+    }
+
+    // `{"conditions": {...}}` plus the settings/mappings/aliases of the new index
+    pub fn body(&self, config: &IndexConfig) -> json::Object {
+        let mut conditions: json::Object = BTreeMap::new();
+        if let Some(ref age) = self.max_age { conditions.insert("max_age".to_string(), age.to_time_value().to_json()); }
+        if let Some(docs) = self.max_docs { conditions.insert("max_docs".to_string(), docs.to_json()); }
+        if let Some(ref size) = self.max_size { conditions.insert("max_size".to_string(), size.to_json()); }
+
+        let mut obj = config.to_object();
+        obj.insert("conditions".to_string(), Json::Object(conditions));
+        obj
+    }
+}
+
+// `conditions` maps each condition, e.g. "[max_docs: 1000]", to whether it was met
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct RolloverResponse {
+    pub acknowledged: bool,
+    pub shards_acknowledged: Option<bool>,
+    pub old_index: String,
+    pub new_index: String,
+    pub rolled_over: bool,
+    pub dry_run: bool,
+    pub conditions: BTreeMap<String, bool>
+}
+
+// returned by both shrink and split
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct ResizeResponse {
+    pub acknowledged: bool,
+    pub shards_acknowledged: Option<bool>,
+    pub index: Option<String>
+}
+
+decodable_response!(RolloverResponse, ResizeResponse);
+
+#[test]
+fn test_rollover_conditions_body() {
+    use std::str::FromStr;
+
+    let age = Timeout::from_str("7d").unwrap();
+    assert_eq!(age.to_time_value(), "604800000ms");
+    assert_eq!(Timeout::from_str("1500").unwrap().to_time_value(), "1500ms");
+
+    let conditions = RolloverConditions::new().max_age(age).max_docs(1000).max_size("50gb");
+    let body = Json::Object(conditions.body(&IndexConfig::new()));
+    let expected = Json::from_str(
+        r#"{"conditions": {"max_age": "604800000ms", "max_docs": 1000, "max_size": "50gb"}}"#).unwrap();
+    assert_eq!(body, expected);
+
+    let body = Json::Object(RolloverConditions::new().body(&IndexConfig::new()));
+    assert_eq!(body, Json::from_str(r#"{"conditions": {}}"#).unwrap());
+}
+
+#[test]
+fn test_rollover_response() {
+    let body = r#"{"acknowledged": true, "shards_acknowledged": true, "old_index": "logs-000001",
+                   "new_index": "logs-000002", "rolled_over": true, "dry_run": false,
+                   "conditions": {"[max_docs: 1000]": true}}"#;
+    let response = RolloverResponse::from_response(body).unwrap();
+    assert_eq!(response.new_index, "logs-000002");
+    assert_eq!(response.conditions.get("[max_docs: 1000]"), Some(&true));
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use rustc_serialize::json::{self, Json, ToJson};
+use hyper::method::Method::{Get, Put, Post, Delete};
+use connection::Connection;
+use transport::RequestOptions;
+use error::{Error, EsResult};
+use response::{FromResponse, AcknowledgedResponse};
+use types::*;
+
+new_query_struct!{ PutScriptRequest(id: String, script_body: json::Object) {
+    fn_path => |self| {
+        vec!["_scripts".to_string(), self.id.to_string()]
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    body => script_body,
+    method => Put
+}}
+
+impl_send!{ PutScriptRequest => AcknowledgedResponse }
+
+new_query_struct!{ GetScriptRequest(id: String) {
+    fn_path => |self| {
+        vec!["_scripts".to_string(), self.id.to_string()]
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ GetScriptRequest => GetScriptResponse }
+
+new_query_struct!{ DeleteScriptRequest(id: String) {
+    fn_path => |self| {
+        vec!["_scripts".to_string(), self.id.to_string()]
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    method => Delete
+}}
+
+impl_send!{ DeleteScriptRequest => AcknowledgedResponse }
+
+new_query_struct!{ SearchTemplateRequest(indices: Option<StringList>, template_body: json::Object) {
+    fn_path => |self| {
+        let mut path: Vec<String> = Vec::new();
+        if let Some(ref indices) = self.indices { path.push(indices.to_string()); }
+        path.push("_search".to_string());
+        path.push("template".to_string());
+        path
+    },
+    query_params => [
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (allow_no_indices: AllowNoIndices, None),
+        (expand_wildcards: ExpandWildcards, None),
+        (preference: Preference, None),
+        (routing: Routing, None)
+    ],
+    body => template_body,
+    method => Post
+}}
+
+impl_send!{ SearchTemplateRequest => Json }
+
+// The response holds the rendered search body under `template_output`.
+new_query_struct!{ RenderTemplateRequest(id: Option<String>, template_body: json::Object) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_render".to_string(), "template".to_string()];
+        if let Some(ref id) = self.id { path.push(id.to_string()); }
+        path
+    },
+    query_params => [],
+    body => template_body,
+    method => Post
+}}
+
+impl_send!{ RenderTemplateRequest => Json }
+
+// `_msearch/template` takes newline-delimited header/body pairs, so like
+// `BulkRequest` it is not built with `new_query_struct!`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MsearchTemplateRequest<'a> {
+    connection: &'a Connection,
+    indices: Option<StringList>,
+    searches: Vec<(json::Object, ScriptRef)>,
+    request_options: RequestOptions
+}
+
+impl<'a> MsearchTemplateRequest<'a> {
+    pub fn new(connection: &'a Connection, indices: Option<StringList>,
+               searches: Vec<(json::Object, ScriptRef)>) -> MsearchTemplateRequest<'a> {
+        MsearchTemplateRequest {
+            connection: connection,
+            indices: indices,
+            searches: searches,
+            request_options: RequestOptions::new()
+        }
+    }
+
+    pub fn get(self) -> MsearchTemplateRequest<'a> { self }
+
+    request_option_setters!{}
+
+    pub fn get_path(&self) -> Vec<String> {
+        let mut path: Vec<String> = Vec::new();
+        if let Some(ref indices) = self.indices { path.push(indices.to_string()); }
+        path.push("_msearch".to_string());
+        path.push("template".to_string());
+        path
+    }
+
+    pub fn execute(&self) -> EsResult<String> {
+        let mut bod = String::new();
+        for &(ref header, ref template) in self.searches.iter() {
+            bod.push_str(&Json::Object(header.clone()).to_string());
+            bod.push_str("\n");
+            bod.push_str(&Json::Object(template.template_body()).to_string());
+            bod.push_str("\n");
+        }
+        self.connection.request(Post, self.get_path(), Vec::new(), Some(bod.as_bytes()), &self.request_options)
+    }
+}
+
+impl_send!{ MsearchTemplateRequest => Json }
+
+// A reference to a script, either inline or stored with `PutScriptRequest`.
+// Serializes to the `{"source"|"id": ..., "lang": ..., "params": ...}` form
+// accepted by update requests, script queries and search templates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptRef {
+    Inline { source: String, lang: Option<Lang>, params: json::Object },
+    Stored { id: ScriptId, params: json::Object }
+}
+
+impl ScriptRef {
+    pub fn inline(source: &str) -> ScriptRef {
+        ScriptRef::Inline { source: source.to_string(), lang: None, params: BTreeMap::new() }
+    }
+
+    pub fn stored<T: Into<ScriptId>>(id: T) -> ScriptRef {
+        ScriptRef::Stored { id: id.into(), params: BTreeMap::new() }
+    }
+
+    // only meaningful for inline scripts; stored scripts carry their own lang
+    pub fn lang<T: Into<Lang>>(self, new_lang: T) -> ScriptRef {
+        match self {
+            ScriptRef::Inline { source, params, .. } =>
+                ScriptRef::Inline { source: source, lang: Some(new_lang.into()), params: params },
+            stored => stored
+        }
+    }
+
+    pub fn param<T: ToJson>(mut self, key: &str, value: T) -> ScriptRef {
+        match self {
+            ScriptRef::Inline { ref mut params, .. } | ScriptRef::Stored { ref mut params, .. } => {
+                params.insert(key.to_string(), value.to_json());
+            }
+        }
+        self
+    }
+
+    // `{"script": {...}}`, the body of an `UpdateRequest`
+    pub fn update_body(&self) -> json::Object {
+        let mut obj: json::Object = BTreeMap::new();
+        obj.insert("script".to_string(), self.to_json());
+        obj
+    }
+
+    // search templates take no `lang`
+    pub fn template_body(&self) -> json::Object {
+        self.to_object(false)
+    }
+
+    fn to_object(&self, with_lang: bool) -> json::Object {
+        let mut obj: json::Object = BTreeMap::new();
+        let params = match *self {
+            ScriptRef::Inline { ref source, ref lang, ref params } => {
+                obj.insert("source".to_string(), source.to_json());
+                if let (true, &Some(ref l)) = (with_lang, lang) {
+                    obj.insert("lang".to_string(), l.get_value().to_json());
+                }
+                params
+            },
+            ScriptRef::Stored { ref id, ref params } => {
+                obj.insert("id".to_string(), id.get_value().to_json());
+                params
+            }
+        };
+        if !params.is_empty() { obj.insert("params".to_string(), Json::Object(params.clone())); }
+        obj
+    }
+}
+
+impl ToJson for ScriptRef {
+    fn to_json(&self) -> Json {
+        Json::Object(self.to_object(true))
+    }
+}
+
+// A script as stored under `_scripts/{id}`, e.g. lang `painless` or, for
+// search templates, `mustache`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredScript {
+    pub lang: String,
+    pub source: String
+}
+
+impl StoredScript {
+    pub fn new(lang: &str, source: &str) -> StoredScript {
+        StoredScript { lang: lang.to_string(), source: source.to_string() }
+    }
+
+    pub fn body(&self) -> json::Object {
+        let mut script: json::Object = BTreeMap::new();
+        script.insert("lang".to_string(), self.lang.to_json());
+        script.insert("source".to_string(), self.source.to_json());
+
+        let mut obj: json::Object = BTreeMap::new();
+        obj.insert("script".to_string(), Json::Object(script));
+        obj
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetScriptResponse {
+    pub id: String,
+    pub found: bool,
+    pub script: Option<StoredScript>
+}
+
+impl FromResponse for GetScriptResponse {
+    fn from_json(json: Json) -> EsResult<GetScriptResponse> {
+        let id = try!(json.find("_id").and_then(|i| i.as_string())
+                      .ok_or(Error::UnexpectedResponse(json.to_string())));
+        // mustache sources are returned as strings or, on older clusters,
+        // under `code`
+        let script = json.find("script").map(|s| StoredScript {
+            lang: s.find("lang").and_then(|l| l.as_string()).unwrap_or("").to_string(),
+            source: match s.find("source").or(s.find("code")) {
+                Some(&Json::String(ref src)) => src.to_string(),
+                Some(other) => other.to_string(),
+                None => String::new()
+            }
+        });
+
+        Ok(GetScriptResponse {
+            id: id.to_string(),
+            found: json.find("found").and_then(|f| f.as_boolean()).unwrap_or(script.is_some()),
+            script: script
+        })
+    }
+}
+
+#[test]
+fn test_script_ref() {
+    let inline = ScriptRef::inline("ctx._source.count += params.n").lang("painless").param("n", 2u64);
+    let expected = Json::from_str(r#"{"source": "ctx._source.count += params.n", "lang": "painless",
+                                      "params": {"n": 2}}"#).unwrap();
+    assert_eq!(inline.to_json(), expected);
+
+    let stored = ScriptRef::stored("by-user".to_string()).param("user", "kimchy");
+    let expected = Json::from_str(r#"{"id": "by-user", "params": {"user": "kimchy"}}"#).unwrap();
+    assert_eq!(Json::Object(stored.template_body()), expected);
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use rustc_serialize::json::{self, Json, ToJson};
+use error::{Error, EsResult};
+use response::FromResponse;
+
+//
+// Typed view over index settings. Settings are always held flattened
+// (`index.number_of_replicas`), whether or not the cluster was asked for
+// `flat_settings`. Keys without a typed field are kept in `raw`.
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSettings {
+    pub number_of_shards: Option<u64>,
+    pub number_of_replicas: Option<u64>,
+    pub refresh_interval: Option<String>,
+    pub max_result_window: Option<u64>,
+    pub raw: BTreeMap<String, Json>
+}
+
+impl IndexSettings {
+    pub fn new() -> IndexSettings {
+        IndexSettings {
+            number_of_shards: None,
+            number_of_replicas: None,
+            refresh_interval: None,
+            max_result_window: None,
+            raw: BTreeMap::new()
+        }
+    }
+
+    pub fn number_of_shards(mut self, shards: u64) -> IndexSettings {
+        self.number_of_shards = Some(shards);
+        self
+    }
+
+    pub fn number_of_replicas(mut self, replicas: u64) -> IndexSettings {
+        self.number_of_replicas = Some(replicas);
+        self
+    }
+
+    // e.g. "1s", or "-1" to disable refreshes
+    pub fn refresh_interval(mut self, interval: &str) -> IndexSettings {
+        self.refresh_interval = Some(interval.to_string());
+        self
+    }
+
+    pub fn max_result_window(mut self, window: u64) -> IndexSettings {
+        self.max_result_window = Some(window);
+        self
+    }
+
+    // Sets any other setting by its flat name. `Json::Null` resets the
+    // setting to its default when updating.
+    pub fn set<T: ToJson>(mut self, key: &str, value: T) -> IndexSettings {
+        self.raw.insert(key.to_string(), value.to_json());
+        self
+    }
+
+    pub fn from_json(json: &Json) -> EsResult<IndexSettings> {
+        let mut flat: BTreeMap<String, Json> = BTreeMap::new();
+        flatten("", json, &mut flat);
+
+        let mut settings = IndexSettings::new();
+        settings.number_of_shards = try!(take_u64(&mut flat, "index.number_of_shards"));
+        settings.number_of_replicas = try!(take_u64(&mut flat, "index.number_of_replicas"));
+        settings.max_result_window = try!(take_u64(&mut flat, "index.max_result_window"));
+        settings.refresh_interval = match flat.remove("index.refresh_interval") {
+            Some(Json::String(s)) => Some(s),
+            Some(Json::I64(i)) => Some(i.to_string()),
+            Some(Json::U64(u)) => Some(u.to_string()),
+            Some(Json::Null) | None => None,
+            Some(other) => return Err(Error::UnexpectedResponse(
+                format!("index.refresh_interval: {}", other)))
+        };
+        settings.raw = flat;
+
+        Ok(settings)
+    }
+}
+
+impl ToJson for IndexSettings {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = self.raw.clone();
+        if let Some(n) = self.number_of_shards { obj.insert("index.number_of_shards".to_string(), n.to_json()); }
+        if let Some(n) = self.number_of_replicas { obj.insert("index.number_of_replicas".to_string(), n.to_json()); }
+        if let Some(ref r) = self.refresh_interval { obj.insert("index.refresh_interval".to_string(), r.to_json()); }
+        if let Some(n) = self.max_result_window { obj.insert("index.max_result_window".to_string(), n.to_json()); }
+        Json::Object(obj)
+    }
+}
+
+// One entry of a `GET /{index}/_settings` response. `defaults` is only
+// present when `include_defaults` was requested.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSettingsEntry {
+    pub settings: IndexSettings,
+    pub defaults: Option<IndexSettings>
+}
+
+impl FromResponse for BTreeMap<String, IndexSettingsEntry> {
+    fn from_json(json: Json) -> EsResult<BTreeMap<String, IndexSettingsEntry>> {
+        let indices = match json {
+            Json::Object(obj) => obj,
+            other => return Err(Error::UnexpectedResponse(other.to_string()))
+        };
+
+        let mut out = BTreeMap::new();
+        for (index, value) in indices.into_iter() {
+            let settings = match value.find("settings") {
+                Some(s) => try!(IndexSettings::from_json(s)),
+                None => IndexSettings::new()
+            };
+            let defaults = match value.find("defaults") {
+                Some(d) => Some(try!(IndexSettings::from_json(d))),
+                None => None
+            };
+            out.insert(index, IndexSettingsEntry { settings: settings, defaults: defaults });
+        }
+        Ok(out)
+    }
+}
+
+// Collapses nested settings objects into dotted keys.
+pub fn flatten(prefix: &str, json: &Json, out: &mut BTreeMap<String, Json>) {
+    match *json {
+        Json::Object(ref obj) => {
+            for (key, value) in obj.iter() {
+                let name = if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+                flatten(&name, value, out);
+            }
+        },
+        ref value => { out.insert(prefix.to_string(), value.clone()); }
+    }
+}
+
+// the cluster returns numeric settings as strings
+fn take_u64(flat: &mut BTreeMap<String, Json>, key: &str) -> EsResult<Option<u64>> {
+    match flat.remove(key) {
+        Some(Json::U64(u)) => Ok(Some(u)),
+        Some(Json::I64(i)) if i >= 0 => Ok(Some(i as u64)),
+        Some(Json::String(s)) => match u64::from_str(&s) {
+            Ok(u) => Ok(Some(u)),
+            Err(_) => Err(Error::UnexpectedResponse(format!("{}: {}", key, s)))
+        },
+        Some(Json::Null) | None => Ok(None),
+        Some(other) => Err(Error::UnexpectedResponse(format!("{}: {}", key, other)))
+    }
+}
+
+#[test]
+fn test_settings_from_nested_and_flat() {
+    let nested = Json::from_str(r#"{"index": {"number_of_replicas": "1", "refresh_interval": "5s",
+                                    "analysis": {"analyzer": {"default": {"type": "simple"}}}}}"#).unwrap();
+    let flat = Json::from_str(r#"{"index.number_of_replicas": "1", "index.refresh_interval": "5s",
+                                  "index.analysis.analyzer.default.type": "simple"}"#).unwrap();
+
+    let a = IndexSettings::from_json(&nested).unwrap();
+    let b = IndexSettings::from_json(&flat).unwrap();
+    assert_eq!(a, b);
+    assert_eq!(a.number_of_replicas, Some(1));
+    assert_eq!(a.refresh_interval, Some("5s".to_string()));
+    assert_eq!(a.raw.get("index.analysis.analyzer.default.type"), Some(&"simple".to_json()));
+}
@@ -0,0 +1,515 @@
+use std::collections::BTreeMap;
+use chrono::{DateTime, TimeZone, UTC};
+use rustc_serialize::json::{self, Json, ToJson};
+use hyper::method::Method::{Get, Put, Post, Delete};
+use connection::Connection;
+use error::{Error, EsResult};
+use response::{FromResponse, AcknowledgedResponse};
+use settings::IndexSettings;
+use types::*;
+
+new_query_struct!{ PutRepositoryRequest(repository: String, repository_body: json::Json) {
+    fn_path => |self| {
+        vec!["_snapshot".to_string(), self.repository.to_string()]
+    },
+    query_params => [
+        (verify: Verify, None),
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    body => repository_body,
+    method => Put
+}}
+
+impl_send!{ PutRepositoryRequest => AcknowledgedResponse }
+
+new_query_struct!{ GetRepositoryRequest(repository: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_snapshot".to_string()];
+        if let Some(ref repository) = self.repository { path.push(repository.to_string()); }
+        path
+    },
+    query_params => [
+        (local: Local, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ GetRepositoryRequest => BTreeMap<String, Repository> }
+
+new_query_struct!{ DeleteRepositoryRequest(repository: StringList) {
+    fn_path => |self| {
+        vec!["_snapshot".to_string(), self.repository.to_string()]
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    method => Delete
+}}
+
+impl_send!{ DeleteRepositoryRequest => AcknowledgedResponse }
+
+// The response lists the nodes that verified the repository.
+new_query_struct!{ VerifyRepositoryRequest(repository: String) {
+    fn_path => |self| {
+        vec!["_snapshot".to_string(), self.repository.to_string(), "_verify".to_string()]
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None),
+        (timeout: Timeout, None)
+    ],
+    method => Post
+}}
+
+impl_send!{ VerifyRepositoryRequest => Json }
+
+new_query_struct!{ CreateSnapshotRequest(repository: String, snapshot: String, snapshot_body: json::Object) {
+    fn_path => |self| {
+        vec!["_snapshot".to_string(), self.repository.to_string(), self.snapshot.to_string()]
+    },
+    query_params => [
+        (wait_for_completion: WaitForCompletion, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    body => snapshot_body,
+    method => Put
+}}
+
+impl_send!{ CreateSnapshotRequest => SnapshotResponse }
+
+new_query_struct!{ GetSnapshotRequest(repository: String, snapshot: StringList) {
+    fn_path => |self| {
+        vec!["_snapshot".to_string(), self.repository.to_string(), self.snapshot.to_string()]
+    },
+    query_params => [
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (verbose: Verbose, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ GetSnapshotRequest => SnapshotList }
+
+new_query_struct!{ SnapshotStatusRequest(repository: Option<String>, snapshot: Option<StringList>) {
+    fn_path => |self| {
+        let mut path: Vec<String> = vec!["_snapshot".to_string()];
+        if let Some(ref repository) = self.repository { path.push(repository.to_string()); }
+        if let Some(ref snapshot) = self.snapshot { path.push(snapshot.to_string()); }
+        path.push("_status".to_string());
+        path
+    },
+    query_params => [
+        (ignore_unavailable: IgnoreUnavailable, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    method => Get
+}}
+
+impl_send!{ SnapshotStatusRequest => SnapshotStatuses }
+
+new_query_struct!{ DeleteSnapshotRequest(repository: String, snapshot: String) {
+    fn_path => |self| {
+        vec!["_snapshot".to_string(), self.repository.to_string(), self.snapshot.to_string()]
+    },
+    query_params => [
+        (master_timeout: MasterTimeout, None)
+    ],
+    method => Delete
+}}
+
+impl_send!{ DeleteSnapshotRequest => AcknowledgedResponse }
+
+new_query_struct!{ RestoreSnapshotRequest(repository: String, snapshot: String, restore_body: json::Object) {
+    fn_path => |self| {
+        vec!["_snapshot".to_string(), self.repository.to_string(), self.snapshot.to_string(),
+             "_restore".to_string()]
+    },
+    query_params => [
+        (wait_for_completion: WaitForCompletion, None),
+        (master_timeout: MasterTimeout, None)
+    ],
+    body => restore_body,
+    method => Post
+}}
+
+impl_send!{ RestoreSnapshotRequest => RestoreResponse }
+
+//
+// repositories
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repository {
+    pub repository_type: String,
+    pub settings: BTreeMap<String, Json>
+}
+
+impl Repository {
+    pub fn new(repository_type: &str) -> Repository {
+        Repository { repository_type: repository_type.to_string(), settings: BTreeMap::new() }
+    }
+
+    pub fn fs(location: &str) -> Repository {
+        Repository::new("fs").setting("location", location)
+    }
+
+    pub fn url(url: &str) -> Repository {
+        Repository::new("url").setting("url", url)
+    }
+
+    // e.g. `compress`, `chunk_size`, or any setting of a plugin repository type
+    pub fn setting<T: ToJson>(mut self, key: &str, value: T) -> Repository {
+        self.settings.insert(key.to_string(), value.to_json());
+        self
+    }
+
+    fn from_json(json: &Json) -> EsResult<Repository> {
+        let repository_type = try!(json.find("type").and_then(|t| t.as_string())
+                                   .ok_or(Error::UnexpectedResponse(format!("repository without a type: {}", json))));
+        let settings = match json.find("settings") {
+            Some(&Json::Object(ref obj)) => obj.clone(),
+            _ => BTreeMap::new()
+        };
+        Ok(Repository { repository_type: repository_type.to_string(), settings: settings })
+    }
+}
+
+impl ToJson for Repository {
+    fn to_json(&self) -> Json {
+        let mut obj: json::Object = BTreeMap::new();
+        obj.insert("type".to_string(), self.repository_type.to_json());
+        obj.insert("settings".to_string(), Json::Object(self.settings.clone()));
+        Json::Object(obj)
+    }
+}
+
+// keyed by repository name
+impl FromResponse for BTreeMap<String, Repository> {
+    fn from_json(json: Json) -> EsResult<BTreeMap<String, Repository>> {
+        let repositories = match json {
+            Json::Object(obj) => obj,
+            other => return Err(Error::UnexpectedResponse(other.to_string()))
+        };
+
+        let mut out = BTreeMap::new();
+        for (name, value) in repositories.iter() {
+            out.insert(name.to_string(), try!(Repository::from_json(value)));
+        }
+        Ok(out)
+    }
+}
+
+//
+// snapshot and restore bodies
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotConfig {
+    pub indices: Option<StringList>,
+    pub ignore_unavailable: Option<bool>,
+    pub include_global_state: Option<bool>,
+    pub partial: Option<bool>
+}
+
+impl SnapshotConfig {
+    pub fn new() -> SnapshotConfig {
+        SnapshotConfig { indices: None, ignore_unavailable: None, include_global_state: None, partial: None }
+    }
+
+    pub fn indices(mut self, indices: StringList) -> SnapshotConfig {
+        self.indices = Some(indices);
+        self
+    }
+
+    pub fn ignore_unavailable(mut self, ignore_unavailable: bool) -> SnapshotConfig {
+        self.ignore_unavailable = Some(ignore_unavailable);
+        self
+    }
+
+    pub fn include_global_state(mut self, include_global_state: bool) -> SnapshotConfig {
+        self.include_global_state = Some(include_global_state);
+        self
+    }
+
+    pub fn partial(mut self, partial: bool) -> SnapshotConfig {
+        self.partial = Some(partial);
+        self
+    }
+
+    pub fn body(&self) -> json::Object {
+        let mut obj: json::Object = BTreeMap::new();
+        if let Some(ref i) = self.indices { obj.insert("indices".to_string(), i.to_string().to_json()); }
+        if let Some(i) = self.ignore_unavailable { obj.insert("ignore_unavailable".to_string(), i.to_json()); }
+        if let Some(g) = self.include_global_state { obj.insert("include_global_state".to_string(), g.to_json()); }
+        if let Some(p) = self.partial { obj.insert("partial".to_string(), p.to_json()); }
+        obj
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestoreConfig {
+    pub snapshot: SnapshotConfig,
+    pub rename_pattern: Option<String>,
+    pub rename_replacement: Option<String>,
+    pub include_aliases: Option<bool>,
+    pub index_settings: Option<IndexSettings>,
+    pub ignore_index_settings: Vec<String>
+}
+
+impl RestoreConfig {
+    pub fn new(snapshot: SnapshotConfig) -> RestoreConfig {
+        RestoreConfig {
+            snapshot: snapshot,
+            rename_pattern: None,
+            rename_replacement: None,
+            include_aliases: None,
+            index_settings: None,
+            ignore_index_settings: Vec::new()
+        }
+    }
+
+    // e.g. `rename("index_(.+)", "restored_index_$1")`
+    pub fn rename(mut self, pattern: &str, replacement: &str) -> RestoreConfig {
+        self.rename_pattern = Some(pattern.to_string());
+        self.rename_replacement = Some(replacement.to_string());
+        self
+    }
+
+    pub fn include_aliases(mut self, include_aliases: bool) -> RestoreConfig {
+        self.include_aliases = Some(include_aliases);
+        self
+    }
+
+    pub fn index_settings(mut self, settings: IndexSettings) -> RestoreConfig {
+        self.index_settings = Some(settings);
+        self
+    }
+
+    pub fn ignore_index_setting(mut self, setting: &str) -> RestoreConfig {
+        self.ignore_index_settings.push(setting.to_string());
+        self
+    }
+
+    pub fn body(&self) -> json::Object {
+        let mut obj = self.snapshot.body();
+        if let Some(ref p) = self.rename_pattern { obj.insert("rename_pattern".to_string(), p.to_json()); }
+        if let Some(ref r) = self.rename_replacement { obj.insert("rename_replacement".to_string(), r.to_json()); }
+        if let Some(a) = self.include_aliases { obj.insert("include_aliases".to_string(), a.to_json()); }
+        if let Some(ref s) = self.index_settings { obj.insert("index_settings".to_string(), s.to_json()); }
+        if !self.ignore_index_settings.is_empty() {
+            obj.insert("ignore_index_settings".to_string(), self.ignore_index_settings.to_json());
+        }
+        obj
+    }
+}
+
+//
+// responses
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotState {
+    InProgress,
+    Success,
+    Failed,
+    Partial,
+    Incompatible,
+    Other(String)
+}
+
+impl<'a> From<&'a str> for SnapshotState {
+    fn from(s: &'a str) -> SnapshotState {
+        match s {
+            "IN_PROGRESS" | "STARTED" => SnapshotState::InProgress,
+            "SUCCESS" => SnapshotState::Success,
+            "FAILED" => SnapshotState::Failed,
+            "PARTIAL" => SnapshotState::Partial,
+            "INCOMPATIBLE" => SnapshotState::Incompatible,
+            other => SnapshotState::Other(other.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotShards {
+    pub total: u64,
+    pub failed: u64,
+    pub successful: u64
+}
+
+impl SnapshotShards {
+    fn from_json(json: &Json) -> SnapshotShards {
+        let count = |key: &str| json.find(key).and_then(|c| c.as_u64()).unwrap_or(0);
+        SnapshotShards { total: count("total"), failed: count("failed"), successful: count("successful") }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotInfo {
+    pub snapshot: String,
+    pub uuid: Option<String>,
+    pub version: Option<String>,
+    pub indices: Vec<String>,
+    pub state: SnapshotState,
+    pub start_time: Option<DateTime<UTC>>,
+    pub end_time: Option<DateTime<UTC>>,
+    pub duration_in_millis: Option<u64>,
+    pub failures: Vec<Json>,
+    pub shards: SnapshotShards
+}
+
+impl SnapshotInfo {
+    fn from_json(json: &Json) -> EsResult<SnapshotInfo> {
+        let snapshot = try!(json.find("snapshot").and_then(|s| s.as_string())
+                            .ok_or(Error::UnexpectedResponse(format!("snapshot without a name: {}", json))));
+        let opt_string = |key: &str| json.find(key).and_then(|v| v.as_string()).map(|v| v.to_string());
+        let time = |key: &str| -> EsResult<Option<DateTime<UTC>>> {
+            match json.find(key).and_then(|t| t.as_i64()) {
+                Some(millis) => millis_to_datetime(millis).map(Some),
+                None => Ok(None)
+            }
+        };
+
+        Ok(SnapshotInfo {
+            snapshot: snapshot.to_string(),
+            uuid: opt_string("uuid"),
+            version: opt_string("version"),
+            indices: match json.find("indices").and_then(|i| i.as_array()) {
+                Some(list) => list.iter().filter_map(|i| i.as_string()).map(|i| i.to_string()).collect(),
+                None => Vec::new()
+            },
+            state: SnapshotState::from(opt_string("state").as_ref().map(|s| s.as_str()).unwrap_or("")),
+            start_time: try!(time("start_time_in_millis")),
+            end_time: try!(time("end_time_in_millis")),
+            duration_in_millis: json.find("duration_in_millis").and_then(|d| d.as_u64()),
+            failures: match json.find("failures").and_then(|f| f.as_array()) {
+                Some(list) => list.clone(),
+                None => Vec::new()
+            },
+            shards: json.find("shards").map(SnapshotShards::from_json)
+                .unwrap_or(SnapshotShards { total: 0, failed: 0, successful: 0 })
+        })
+    }
+}
+
+fn millis_to_datetime(millis: i64) -> EsResult<DateTime<UTC>> {
+    let invalid = || Error::UnexpectedResponse(format!("invalid timestamp: {}", millis));
+    if millis < 0 { return Err(invalid()); }
+    UTC.timestamp_opt(millis / 1000, ((millis % 1000) * 1_000_000) as u32).single().ok_or_else(invalid)
+}
+
+// `snapshot` is only present when `wait_for_completion` was set; otherwise
+// the cluster just reports `accepted`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotResponse {
+    pub accepted: Option<bool>,
+    pub snapshot: Option<SnapshotInfo>
+}
+
+impl FromResponse for SnapshotResponse {
+    fn from_json(json: Json) -> EsResult<SnapshotResponse> {
+        Ok(SnapshotResponse {
+            accepted: json.find("accepted").and_then(|a| a.as_boolean()),
+            snapshot: match json.find("snapshot") {
+                Some(s) => Some(try!(SnapshotInfo::from_json(s))),
+                None => None
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotList(pub Vec<SnapshotInfo>);
+
+impl FromResponse for SnapshotList {
+    fn from_json(json: Json) -> EsResult<SnapshotList> {
+        let mut out = Vec::new();
+        if let Some(list) = json.find("snapshots").and_then(|s| s.as_array()) {
+            for s in list.iter() {
+                out.push(try!(SnapshotInfo::from_json(s)));
+            }
+        }
+        Ok(SnapshotList(out))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotStatus {
+    pub snapshot: String,
+    pub repository: String,
+    pub state: SnapshotState,
+    pub include_global_state: Option<bool>,
+    // per-stage shard counts, plus per-index and file/byte progress
+    pub shards_stats: Json,
+    pub stats: Json,
+    pub indices: Json
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotStatuses(pub Vec<SnapshotStatus>);
+
+impl FromResponse for SnapshotStatuses {
+    fn from_json(json: Json) -> EsResult<SnapshotStatuses> {
+        let mut out = Vec::new();
+        if let Some(list) = json.find("snapshots").and_then(|s| s.as_array()) {
+            for s in list.iter() {
+                let string = |key: &str| s.find(key).and_then(|v| v.as_string()).unwrap_or("").to_string();
+                let raw = |key: &str| s.find(key).map(|v| v.clone()).unwrap_or(Json::Null);
+                out.push(SnapshotStatus {
+                    snapshot: string("snapshot"),
+                    repository: string("repository"),
+                    state: SnapshotState::from(string("state").as_str()),
+                    include_global_state: s.find("include_global_state").and_then(|g| g.as_boolean()),
+                    shards_stats: raw("shards_stats"),
+                    stats: raw("stats"),
+                    indices: raw("indices")
+                });
+            }
+        }
+        Ok(SnapshotStatuses(out))
+    }
+}
+
+// As with snapshot creation, `snapshot` (with the restored indices and shard
+// counts) is only returned when `wait_for_completion` was set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestoreResponse {
+    pub accepted: Option<bool>,
+    pub indices: Vec<String>,
+    pub shards: Option<SnapshotShards>
+}
+
+impl FromResponse for RestoreResponse {
+    fn from_json(json: Json) -> EsResult<RestoreResponse> {
+        let snapshot = json.find("snapshot");
+        Ok(RestoreResponse {
+            accepted: json.find("accepted").and_then(|a| a.as_boolean()),
+            indices: match snapshot.and_then(|s| s.find("indices")).and_then(|i| i.as_array()) {
+                Some(list) => list.iter().filter_map(|i| i.as_string()).map(|i| i.to_string()).collect(),
+                None => Vec::new()
+            },
+            shards: snapshot.and_then(|s| s.find("shards")).map(SnapshotShards::from_json)
+        })
+    }
+}
+
+#[test]
+fn test_snapshot_info() {
+    let json = Json::from_str(r#"{"snapshot": "nightly-1", "indices": ["logs-1"], "state": "SUCCESS",
+                                  "start_time_in_millis": 1500000000123, "end_time_in_millis": 1500000060000,
+                                  "shards": {"total": 5, "failed": 0, "successful": 5}}"#).unwrap();
+    let info = SnapshotInfo::from_json(&json).unwrap();
+    assert_eq!(info.state, SnapshotState::Success);
+    assert_eq!(info.indices, vec!["logs-1".to_string()]);
+    assert_eq!(info.start_time, Some(UTC.timestamp(1500000000, 123000000)));
+    assert_eq!(info.shards.successful, 5);
+
+    let json = Json::from_str(r#"{"snapshot": "nightly-2", "state": "IN_PROGRESS", "start_time_in_millis": -1}"#).unwrap();
+    assert!(SnapshotInfo::from_json(&json).is_err());
+
+    let list = SnapshotList::from_response(r#"{"snapshots": [{"snapshot": "nightly-1", "state": "SUCCESS"}]}"#).unwrap();
+    assert_eq!(list.0[0].snapshot, "nightly-1");
+}
@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+use rustc_serialize::json::Json;
+use error::{Error, EsResult};
+use response::{FromResponse, ShardStatistics, decode_json, find_field, find_string};
+
+//
+// _stats
+//
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct DocsStats {
+    pub count: u64,
+    pub deleted: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct StoreStats {
+    pub size_in_bytes: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct IndexingStats {
+    pub index_total: u64,
+    pub index_time_in_millis: u64,
+    pub index_current: u64,
+    pub delete_total: u64,
+    pub delete_time_in_millis: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct SearchStats {
+    pub query_total: u64,
+    pub query_time_in_millis: u64,
+    pub fetch_total: u64,
+    pub fetch_time_in_millis: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct MergeStats {
+    pub current: u64,
+    pub total: u64,
+    pub total_time_in_millis: u64,
+    pub total_docs: u64,
+    pub total_size_in_bytes: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct RefreshStats {
+    pub total: u64,
+    pub total_time_in_millis: u64
+}
+
+// Sections are `None` when excluded by the requested metrics.
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct IndexStats {
+    pub docs: Option<DocsStats>,
+    pub store: Option<StoreStats>,
+    pub indexing: Option<IndexingStats>,
+    pub search: Option<SearchStats>,
+    pub merges: Option<MergeStats>,
+    pub refresh: Option<RefreshStats>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct IndexStatsGroup {
+    pub primaries: IndexStats,
+    pub total: IndexStats
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndicesStatsResponse {
+    pub shards: ShardStatistics,
+    pub all: IndexStatsGroup,
+    pub indices: BTreeMap<String, IndexStatsGroup>
+}
+
+impl FromResponse for IndicesStatsResponse {
+    fn from_json(json: Json) -> EsResult<IndicesStatsResponse> {
+        Ok(IndicesStatsResponse {
+            shards: try!(ShardStatistics::from_json(try!(find_field(&json, "_shards")))),
+            all: try!(decode_json(try!(find_field(&json, "_all")).clone())),
+            indices: match json.find("indices") {
+                Some(indices) => try!(decode_json(indices.clone())),
+                None => BTreeMap::new()
+            }
+        })
+    }
+}
+
+//
+// _segments
+//
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct ShardRouting {
+    pub state: String,
+    pub primary: bool,
+    pub node: Option<String>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct Segment {
+    pub generation: u64,
+    pub num_docs: u64,
+    pub deleted_docs: u64,
+    pub size_in_bytes: u64,
+    pub memory_in_bytes: Option<u64>,
+    pub committed: bool,
+    pub search: bool,
+    pub version: String,
+    pub compound: Option<bool>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct ShardSegments {
+    pub routing: ShardRouting,
+    pub num_committed_segments: u64,
+    pub num_search_segments: u64,
+    pub segments: BTreeMap<String, Segment>
+}
+
+// `shards` is keyed by shard number, with one entry per copy of the shard
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct IndexSegments {
+    pub shards: BTreeMap<String, Vec<ShardSegments>>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentsResponse {
+    pub shards: ShardStatistics,
+    pub indices: BTreeMap<String, IndexSegments>
+}
+
+impl FromResponse for SegmentsResponse {
+    fn from_json(json: Json) -> EsResult<SegmentsResponse> {
+        Ok(SegmentsResponse {
+            shards: try!(ShardStatistics::from_json(try!(find_field(&json, "_shards")))),
+            indices: try!(decode_json(try!(find_field(&json, "indices")).clone()))
+        })
+    }
+}
+
+//
+// _recovery
+//
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct RecoveryBytes {
+    pub total_in_bytes: u64,
+    pub reused_in_bytes: u64,
+    pub recovered_in_bytes: u64,
+    pub percent: String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct RecoveryFiles {
+    pub total: u64,
+    pub reused: u64,
+    pub recovered: u64,
+    pub percent: String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub struct RecoveryTranslog {
+    pub recovered: u64,
+    pub total: i64,
+    pub percent: String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardRecovery {
+    pub id: u64,
+    // e.g. "STORE", "PEER", "SNAPSHOT"
+    pub recovery_type: String,
+    // e.g. "INIT", "INDEX", "TRANSLOG", "DONE"
+    pub stage: String,
+    pub primary: bool,
+    pub total_time_in_millis: u64,
+    pub source: Json,
+    pub target: Json,
+    pub bytes: RecoveryBytes,
+    pub files: RecoveryFiles,
+    pub translog: Option<RecoveryTranslog>
+}
+
+impl ShardRecovery {
+    fn from_json(json: &Json) -> EsResult<ShardRecovery> {
+        let index = try!(find_field(json, "index"));
+        Ok(ShardRecovery {
+            id: try!(find_field(json, "id").and_then(|i| i.as_u64()
+                                               .ok_or(Error::UnexpectedResponse(format!("shard id: {}", i))))),
+            recovery_type: try!(find_string(json, "type")),
+            stage: try!(find_string(json, "stage")),
+            primary: json.find("primary").and_then(|p| p.as_boolean()).unwrap_or(false),
+            total_time_in_millis: json.find("total_time_in_millis").and_then(|t| t.as_u64()).unwrap_or(0),
+            source: json.find("source").map(|s| s.clone()).unwrap_or(Json::Null),
+            target: json.find("target").map(|t| t.clone()).unwrap_or(Json::Null),
+            bytes: try!(decode_json(try!(find_field(index, "size")).clone())),
+            files: try!(decode_json(try!(find_field(index, "files")).clone())),
+            translog: match json.find("translog") {
+                Some(t) => Some(try!(decode_json(t.clone()))),
+                None => None
+            }
+        })
+    }
+}
+
+// keyed by index name
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryResponse {
+    pub indices: BTreeMap<String, Vec<ShardRecovery>>
+}
+
+impl FromResponse for RecoveryResponse {
+    fn from_json(json: Json) -> EsResult<RecoveryResponse> {
+        let indices = match json {
+            Json::Object(obj) => obj,
+            other => return Err(Error::UnexpectedResponse(other.to_string()))
+        };
+
+        let mut out = BTreeMap::new();
+        for (index, value) in indices.iter() {
+            let mut shards = Vec::new();
+            if let Some(list) = value.find("shards").and_then(|s| s.as_array()) {
+                for shard in list.iter() {
+                    shards.push(try!(ShardRecovery::from_json(shard)));
+                }
+            }
+            out.insert(index.to_string(), shards);
+        }
+        Ok(RecoveryResponse { indices: out })
+    }
+}
+
+#[test]
+fn test_indices_stats_response() {
+    let body = r#"{"_shards": {"total": 2, "successful": 2, "failed": 0},
+                   "_all": {"primaries": {"docs": {"count": 10, "deleted": 1}},
+                            "total": {"docs": {"count": 20, "deleted": 2}, "store": {"size_in_bytes": 4096}}},
+                   "indices": {"logs": {"primaries": {}, "total": {"refresh": {"total": 3, "total_time_in_millis": 5}}}}}"#;
+    let stats = IndicesStatsResponse::from_response(body).unwrap();
+    assert_eq!(stats.all.primaries.docs, Some(DocsStats { count: 10, deleted: 1 }));
+    assert_eq!(stats.all.total.store, Some(StoreStats { size_in_bytes: 4096 }));
+    assert_eq!(stats.all.primaries.store, None);
+    assert_eq!(stats.indices.get("logs").unwrap().total.refresh, Some(RefreshStats { total: 3, total_time_in_millis: 5 }));
+
+    assert!(IndicesStatsResponse::from_response(r#"{"_shards": {"total": 0, "successful": 0, "failed": 0}}"#).is_err());
+}
+
+#[test]
+fn test_segments_and_recovery_responses() {
+    let body = r#"{"_shards": {"total": 1, "successful": 1, "failed": 0},
+                   "indices": {"logs": {"shards": {"0": [{
+                       "routing": {"state": "STARTED", "primary": true, "node": "n1"},
+                       "num_committed_segments": 1, "num_search_segments": 1,
+                       "segments": {"_0": {"generation": 0, "num_docs": 5, "deleted_docs": 0, "size_in_bytes": 900,
+                                           "committed": true, "search": true, "version": "7.0.0"}}}]}}}}"#;
+    let segments = SegmentsResponse::from_response(body).unwrap();
+    let shard = &segments.indices.get("logs").unwrap().shards.get("0").unwrap()[0];
+    assert!(shard.routing.primary);
+    assert_eq!(shard.segments.get("_0").unwrap().num_docs, 5);
+
+    let body = r#"{"logs": {"shards": [{
+                       "id": 0, "type": "PEER", "stage": "DONE", "primary": false, "total_time_in_millis": 12,
+                       "source": {"name": "n1"}, "target": {"name": "n2"},
+                       "index": {"size": {"total_in_bytes": 100, "reused_in_bytes": 0, "recovered_in_bytes": 100,
+                                          "percent": "100.0%"},
+                                 "files": {"total": 3, "reused": 0, "recovered": 3, "percent": "100.0%"}}}]}}"#;
+    let recovery = RecoveryResponse::from_response(body).unwrap();
+    let shard = &recovery.indices.get("logs").unwrap()[0];
+    assert_eq!(shard.recovery_type, "PEER");
+    assert_eq!(shard.files.recovered, 3);
+    assert_eq!(shard.translog, None);
+
+    assert!(RecoveryResponse::from_response(r#"{"logs": {"shards": [{"id": 0, "type": 1}]}}"#).is_err());
+}
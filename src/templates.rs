@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use rustc_serialize::json::{self, Json, ToJson};
+use config::IndexConfig;
+use error::{Error, EsResult};
+use response::FromResponse;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexTemplate {
+    pub index_patterns: Vec<String>,
+    // pre-6.0 clusters take a single `template` pattern instead
+    pub template: Option<String>,
+    pub order: Option<i64>,
+    pub version: Option<i64>,
+    pub config: IndexConfig
+}
+
+impl IndexTemplate {
+    pub fn new(index_patterns: Vec<String>, config: IndexConfig) -> IndexTemplate {
+        IndexTemplate {
+            index_patterns: index_patterns,
+            template: None,
+            order: None,
+            version: None,
+            config: config
+        }
+    }
+
+    pub fn with_template(template: &str, config: IndexConfig) -> IndexTemplate {
+        let mut t = IndexTemplate::new(Vec::new(), config);
+        t.template = Some(template.to_string());
+        t
+    }
+
+    pub fn order(mut self, order: i64) -> IndexTemplate {
+        self.order = Some(order);
+        self
+    }
+
+    pub fn version(mut self, version: i64) -> IndexTemplate {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn to_object(&self) -> json::Object {
+        let mut obj = self.config.to_object();
+        if !self.index_patterns.is_empty() { obj.insert("index_patterns".to_string(), self.index_patterns.to_json()); }
+        if let Some(ref t) = self.template { obj.insert("template".to_string(), t.to_json()); }
+        if let Some(o) = self.order { obj.insert("order".to_string(), o.to_json()); }
+        if let Some(v) = self.version { obj.insert("version".to_string(), v.to_json()); }
+        obj
+    }
+
+    pub fn from_json(json: &Json) -> EsResult<IndexTemplate> {
+        let config = try!(IndexConfig::from_json(json));
+        let mut template = IndexTemplate::new(Vec::new(), config);
+
+        match json.find("index_patterns") {
+            Some(&Json::Array(ref patterns)) => {
+                for p in patterns.iter() {
+                    match p.as_string() {
+                        Some(s) => template.index_patterns.push(s.to_string()),
+                        None => return Err(Error::UnexpectedResponse(format!("index_patterns: {}", p)))
+                    }
+                }
+            },
+            Some(&Json::String(ref s)) => template.index_patterns.push(s.to_string()),
+            Some(other) => return Err(Error::UnexpectedResponse(format!("index_patterns: {}", other))),
+            None => {}
+        }
+        template.template = json.find("template").and_then(|t| t.as_string()).map(|t| t.to_string());
+        template.order = json.find("order").and_then(|o| o.as_i64());
+        template.version = json.find("version").and_then(|v| v.as_i64());
+
+        Ok(template)
+    }
+}
+
+impl ToJson for IndexTemplate {
+    fn to_json(&self) -> Json {
+        Json::Object(self.to_object())
+    }
+}
+
+// `GET /_template` responses, keyed by template name
+impl FromResponse for BTreeMap<String, IndexTemplate> {
+    fn from_json(json: Json) -> EsResult<BTreeMap<String, IndexTemplate>> {
+        let templates = match json {
+            Json::Object(obj) => obj,
+            other => return Err(Error::UnexpectedResponse(other.to_string()))
+        };
+
+        let mut out = BTreeMap::new();
+        for (name, value) in templates.iter() {
+            out.insert(name.to_string(), try!(IndexTemplate::from_json(value)));
+        }
+        Ok(out)
+    }
+}
+
+#[test]
+fn test_index_template_round_trip() {
+    use settings::IndexSettings;
+
+    let template = IndexTemplate::new(vec!["logs-*".to_string()],
+                                      IndexConfig::new().settings(IndexSettings::new().number_of_shards(1)))
+        .order(2)
+        .version(7);
+    let json = template.to_json();
+    assert_eq!(json.find("index_patterns"), Some(&vec!["logs-*".to_string()].to_json()));
+    assert_eq!(IndexTemplate::from_json(&json).unwrap(), template);
+
+    // pre-6.0 clusters return a single pattern string
+    let old = Json::from_str(r#"{"template": "logs-*", "order": 0, "settings": {}}"#).unwrap();
+    let parsed = IndexTemplate::from_json(&old).unwrap();
+    assert_eq!(parsed.template, Some("logs-*".to_string()));
+    assert!(parsed.index_patterns.is_empty());
+}
+
+#[test]
+fn test_template_requests() {
+    use hyper::method::Method::{Get, Put, Head, Delete};
+    use mock::{MockTransport, Expectation};
+    use types::StringList;
+
+    let template = IndexTemplate::new(vec!["logs-*".to_string()], IndexConfig::new()).order(1);
+    let mock = MockTransport::new();
+    mock.expect(Expectation::new(Put, "/_template/logs").body(template.to_json())
+                .respond(200, r#"{"acknowledged": true}"#));
+    mock.expect(Expectation::new(Get, "/_template/logs")
+                .respond(200, r#"{"logs": {"index_patterns": ["logs-*"], "order": 1}}"#));
+    mock.expect(Expectation::new(Head, "/_template/missing").respond(404, ""));
+    mock.expect(Expectation::new(Delete, "/_template/logs").respond(200, r#"{"acknowledged": true}"#));
+
+    let client = mock.client();
+    assert!(client.indices.put_template("logs", &template).send().unwrap().acknowledged);
+    let templates = client.indices.get_template(Some(string_list!["logs"])).send().unwrap();
+    assert_eq!(templates.get("logs"), Some(&template));
+    assert!(!client.indices.exists_template(string_list!["missing"]).send().unwrap());
+    assert!(client.indices.delete_template("logs").send().unwrap().acknowledged);
+    mock.verify();
+}
@@ -0,0 +1,383 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use openssl::crypto::hash::Type as HashType;
+use openssl::nid::Nid;
+use openssl::ssl::{Ssl, SslContext, SslStream, SSL_VERIFY_NONE, SSL_VERIFY_PEER};
+use openssl::ssl::SslMethod::Sslv23;
+use openssl::x509::{X509, X509FileType};
+use rustc_serialize::hex::ToHex;
+use error::{Error, EsResult};
+
+//
+// TLS settings for https hosts: the CAs to trust, a client certificate for
+// mutual TLS, and pinned server certificates. By default the server must
+// present a certificate signed by one of the system's CAs and issued for
+// the host being connected to.
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+    ca_files: Vec<PathBuf>,
+    ca_pems: Vec<Vec<u8>>,
+    client_cert: Option<(PathBuf, PathBuf)>,
+    // lowercase hex sha256 fingerprints, without separators
+    pins: Vec<String>,
+    accept_invalid_certs: bool
+}
+
+impl TlsConfig {
+    pub fn new() -> TlsConfig {
+        TlsConfig { ca_files: Vec::new(), ca_pems: Vec::new(), client_cert: None, pins: Vec::new(), accept_invalid_certs: false }
+    }
+
+    // Trusts the CAs in a PEM bundle instead of the system's.
+    pub fn ca_file<P: AsRef<Path>>(mut self, path: P) -> TlsConfig {
+        self.ca_files.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    // Trusts the CAs in a PEM bundle held in memory, as `ca_file` does.
+    pub fn ca_pem(mut self, pem: &[u8]) -> TlsConfig {
+        self.ca_pems.push(pem.to_vec());
+        self
+    }
+
+    // A PEM certificate and private key to present to servers that ask for
+    // one.
+    pub fn client_cert<P: AsRef<Path>, Q: AsRef<Path>>(mut self, cert: P, key: Q) -> TlsConfig {
+        self.client_cert = Some((cert.as_ref().to_path_buf(), key.as_ref().to_path_buf()));
+        self
+    }
+
+    // Only accepts servers whose certificate has one of the pinned sha256
+    // fingerprints, in hex with or without colons, as printed by
+    // `openssl x509 -noout -fingerprint -sha256`. Pinned certificates are
+    // trusted as they are, so self-signed ones need no CA.
+    pub fn pin_sha256(mut self, fingerprint: &str) -> TlsConfig {
+        self.pins.push(fingerprint.chars().filter(|&c| c != ':').collect::<String>().to_lowercase());
+        self
+    }
+
+    // Accepts any certificate. Only for local development; the connection is
+    // encrypted but the server is not authenticated.
+    pub fn danger_accept_invalid_certs(mut self) -> TlsConfig {
+        self.accept_invalid_certs = true;
+        self
+    }
+
+    // The openssl context for new connections. Fails if a certificate, key
+    // or pin can't be used.
+    pub fn context(&self) -> EsResult<SslContext> {
+        for pin in self.pins.iter() {
+            if pin.len() != 64 || !pin.chars().all(|c| c.is_digit(16)) {
+                return Err(Error::Tls(format!("invalid sha256 fingerprint: {}", pin)));
+            }
+        }
+
+        let mut context = try!(SslContext::new(Sslv23).map_err(tls_error));
+        if self.accept_invalid_certs || !self.pins.is_empty() {
+            // pins are checked by `verify_peer` once the handshake is done
+            context.set_verify(SSL_VERIFY_NONE, None);
+        } else {
+            context.set_verify(SSL_VERIFY_PEER, None);
+        }
+
+        if self.ca_files.is_empty() && self.ca_pems.is_empty() {
+            try!(context.set_default_verify_paths().map_err(tls_error));
+        }
+        for ca in self.ca_files.iter() {
+            try!(context.set_CA_file(ca).map_err(tls_error));
+        }
+        for pem in self.ca_pems.iter() {
+            try!(load_ca_pem(&mut context, pem));
+        }
+
+        if let Some((ref cert, ref key)) = self.client_cert {
+            try!(context.set_certificate_file(cert, X509FileType::PEM).map_err(tls_error));
+            try!(context.set_private_key_file(key, X509FileType::PEM).map_err(tls_error));
+            try!(context.check_private_key().map_err(tls_error));
+        }
+        Ok(context)
+    }
+
+    // Checks the certificate the server presented against the pins if there
+    // are any, or else against `host`. openssl has already checked its
+    // signature.
+    pub fn verify_peer(&self, host: &str, cert: Option<X509>) -> EsResult<()> {
+        if self.accept_invalid_certs { return Ok(()); }
+        let cert = match cert {
+            Some(cert) => cert,
+            None => return Err(Error::Tls("server presented no certificate".to_string()))
+        };
+
+        if !self.pins.is_empty() {
+            let fingerprint = cert.fingerprint(HashType::SHA256).map(|f| f.to_hex()).unwrap_or_default();
+            if self.pins.iter().any(|pin| *pin == fingerprint) { return Ok(()); }
+            return Err(Error::Tls(format!("certificate fingerprint {} is not pinned", fingerprint)));
+        }
+
+        if CertNames::of(&cert).matches(host) { return Ok(()); }
+        Err(Error::Tls(format!("certificate is not valid for {}", host)))
+    }
+}
+
+// A `TlsConfig` ready to connect with. Its openssl context is built once,
+// then shared by every connection.
+pub struct TlsConnector {
+    config: TlsConfig,
+    context: Mutex<Option<SslContext>>
+}
+
+impl TlsConnector {
+    // Builds the context on first use.
+    pub fn new(config: TlsConfig) -> TlsConnector {
+        TlsConnector { config: config, context: Mutex::new(None) }
+    }
+
+    // Builds the context now. Fails if a certificate, key or pin can't be
+    // used.
+    pub fn build(config: TlsConfig) -> EsResult<TlsConnector> {
+        let context = try!(config.context());
+        Ok(TlsConnector { config: config, context: Mutex::new(Some(context)) })
+    }
+
+    // Performs the handshake with `host` over `stream` and verifies the
+    // server.
+    pub fn connect<S: Read + Write>(&self, host: &str, stream: S) -> EsResult<SslStream<S>> {
+        let ssl = {
+            let mut context = self.context.lock().unwrap();
+            if context.is_none() {
+                *context = Some(try!(self.config.context()));
+            }
+            try!(Ssl::new(context.as_ref().unwrap()).map_err(tls_error))
+        };
+        try!(ssl.set_hostname(host).map_err(tls_error));
+        let stream = try!(SslStream::new_from(ssl, stream).map_err(tls_error));
+        try!(self.config.verify_peer(host, stream.ssl().get_peer_certificate()));
+        Ok(stream)
+    }
+}
+
+// The names a certificate is issued for: its subject alternative names and
+// the common name of its subject.
+#[derive(Debug, Clone, PartialEq)]
+struct CertNames {
+    dns: Vec<String>,
+    ips: Vec<IpAddr>,
+    common_name: Option<String>
+}
+
+impl CertNames {
+    fn of(cert: &X509) -> CertNames {
+        let mut names = CertNames { dns: Vec::new(), ips: Vec::new(), common_name: None };
+        if let Some(alt_names) = cert.subject_alt_names() {
+            for name in alt_names.iter() {
+                if let Some(dns) = name.dnsname() { names.dns.push(dns.to_string()); }
+                match name.ipaddress() {
+                    Some(ip) if ip.len() == 4 => names.ips.push(IpAddr::V4(Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]))),
+                    Some(ip) if ip.len() == 16 => {
+                        let mut segments = [0u16; 8];
+                        for (i, pair) in ip.chunks(2).enumerate() {
+                            segments[i] = (pair[0] as u16) << 8 | pair[1] as u16;
+                        }
+                        names.ips.push(IpAddr::V6(Ipv6Addr::new(segments[0], segments[1], segments[2], segments[3],
+                                                                segments[4], segments[5], segments[6], segments[7])));
+                    },
+                    _ => ()
+                }
+            }
+        }
+        names.common_name = cert.subject_name().text_by_nid(Nid::CN).map(|cn| cn.to_string());
+        names
+    }
+
+    // Ip addresses must be among the alternative names. Host names must
+    // match a DNS alternative name, or the common name if there are none.
+    fn matches(&self, host: &str) -> bool {
+        let host = host.trim_matches(&['[', ']'][..]);
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return self.ips.contains(&ip);
+        }
+        if self.dns.is_empty() {
+            return self.common_name.as_ref().map(|cn| dns_matches(cn, host)).unwrap_or(false);
+        }
+        self.dns.iter().any(|name| dns_matches(name, host))
+    }
+}
+
+// A wildcard only stands for the whole leftmost label, and must be followed
+// by at least two labels, so `*.com` matches nothing.
+fn dns_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim_matches('.').to_lowercase();
+    let host = host.trim_matches('.').to_lowercase();
+    if pattern.starts_with("*.") {
+        let suffix = &pattern[1..];
+        if suffix[1..].split('.').filter(|label| !label.is_empty()).count() < 2 { return false; }
+        match host.find('.') {
+            Some(dot) => dot > 0 && &host[dot..] == suffix,
+            None => false
+        }
+    } else {
+        pattern == host
+    }
+}
+
+static TEMP_CA_FILES: AtomicUsize = AtomicUsize::new(0);
+
+// openssl only loads CAs to trust from files, so an in-memory bundle is
+// written to a temporary one, removed once loaded.
+fn load_ca_pem(context: &mut SslContext, pem: &[u8]) -> EsResult<()> {
+    try!(X509::from_pem(&mut &pem[..]).map_err(tls_error));
+    let name = format!("elasticsearch-rs-ca-{}-{}.pem", process::id(), TEMP_CA_FILES.fetch_add(1, Ordering::SeqCst));
+    let path = env::temp_dir().join(name);
+    let written = {
+        let mut file = try!(OpenOptions::new().write(true).create_new(true).open(&path).map_err(tls_error));
+        file.write_all(pem).map_err(tls_error)
+    };
+    let loaded = written.and_then(|_| context.set_CA_file(&path).map_err(tls_error));
+    let _ = fs::remove_file(&path);
+    loaded
+}
+
+fn tls_error<E: ToString>(err: E) -> Error {
+    Error::Tls(err.to_string())
+}
+
+#[cfg(test)]
+const SERVER_FINGERPRINT: &'static str =
+    "18:B9:C8:3F:ED:83:35:99:81:B1:33:62:7E:27:77:E9:DA:FC:3F:A9:C6:3B:BF:0D:4F:A4:2B:B2:D0:AE:29:DB";
+
+#[cfg(test)]
+fn cert(name: &str) -> String {
+    format!("{}/tests/certs/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+// Serves `{}` over TLS with the test server certificate, optionally
+// requiring a client certificate signed by the test CA. It listens on every
+// loopback address, though the certificate is only for localhost and
+// 127.0.0.1.
+#[cfg(test)]
+fn start_server(require_client_cert: bool) -> String {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use openssl::ssl::{SslStream, SSL_VERIFY_FAIL_IF_NO_PEER_CERT};
+
+    let listener = TcpListener::bind("0.0.0.0:0").unwrap();
+    let url = format!("https://localhost:{}", listener.local_addr().unwrap().port());
+    thread::spawn(move || {
+        let mut context = SslContext::new(Sslv23).unwrap();
+        context.set_certificate_file(&cert("server.pem"), X509FileType::PEM).unwrap();
+        context.set_private_key_file(&cert("server.key"), X509FileType::PEM).unwrap();
+        if require_client_cert {
+            context.set_CA_file(&cert("ca.pem")).unwrap();
+            context.set_verify(SSL_VERIFY_PEER | SSL_VERIFY_FAIL_IF_NO_PEER_CERT, None);
+        }
+        for tcp in listener.incoming() {
+            let mut stream = match SslStream::new_server(&context, tcp.unwrap()) {
+                Ok(stream) => stream,
+                Err(_) => continue
+            };
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend(buf[..n].iter().cloned())
+                }
+            }
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}");
+        }
+    });
+    url
+}
+
+#[cfg(test)]
+fn get(url: &str, tls: TlsConfig) -> bool {
+    use std::sync::Arc;
+    use hyper::method::Method::Get;
+    use client::Client;
+    use transport::{HyperTransport, RequestOptions};
+
+    let transport = HyperTransport::new().with_tls(tls).unwrap();
+    let client = Client::new_with_transport(url.parse().unwrap(), Arc::new(transport));
+    client.connection.request(Get, Vec::new(), Vec::new(), None, &RequestOptions::new()).is_ok()
+}
+
+#[test]
+fn test_server_verification() {
+    let url = start_server(false);
+    assert!(get(&url, TlsConfig::new().ca_file(cert("ca.pem"))));
+    let mut ca = Vec::new();
+    fs::File::open(cert("ca.pem")).unwrap().read_to_end(&mut ca).unwrap();
+    assert!(get(&url, TlsConfig::new().ca_pem(&ca)));
+    assert!(!get(&url, TlsConfig::new()));
+    assert!(get(&url, TlsConfig::new().danger_accept_invalid_certs()));
+    assert!(get(&url, TlsConfig::new().pin_sha256(SERVER_FINGERPRINT)));
+    assert!(!get(&url, TlsConfig::new().pin_sha256(&SERVER_FINGERPRINT.replace("18:", "19:"))));
+}
+
+#[test]
+fn test_hostname_verification() {
+    let url = start_server(false);
+    let other_host = url.replace("localhost", "127.0.0.2");
+    let ca = TlsConfig::new().ca_file(cert("ca.pem"));
+    assert!(get(&url.replace("localhost", "127.0.0.1"), ca.clone()));
+    assert!(!get(&other_host, ca));
+
+    // pinned certificates are trusted whatever host they're for
+    assert!(get(&other_host, TlsConfig::new().pin_sha256(SERVER_FINGERPRINT)));
+    assert!(get(&other_host, TlsConfig::new().danger_accept_invalid_certs()));
+}
+
+#[test]
+fn test_cert_names() {
+    let names = CertNames {
+        dns: vec!["*.example.com".to_string(), "localhost".to_string()],
+        ips: vec!["127.0.0.1".parse().unwrap(), "::1".parse().unwrap()],
+        common_name: Some("elasticsearch".to_string())
+    };
+    assert!(names.matches("es1.example.com"));
+    assert!(names.matches("LOCALHOST"));
+    assert!(names.matches("[::1]"));
+    assert!(!names.matches("a.es1.example.com"));
+    assert!(!names.matches("example.com"));
+    assert!(!names.matches("127.0.0.2"));
+    // the common name only counts without DNS names
+    assert!(!names.matches("elasticsearch"));
+    assert!(CertNames { dns: Vec::new(), ..names }.matches("elasticsearch"));
+
+    // wildcards must leave at least two labels
+    assert!(!dns_matches("*.com", "example.com"));
+    assert!(!dns_matches("*.", "com"));
+    assert!(dns_matches("*.example.com.", "es1.Example.com"));
+
+    let server = CertNames::of(&X509::from_pem(&mut fs::File::open(cert("server.pem")).unwrap()).unwrap());
+    assert_eq!(server.dns, vec!["localhost".to_string()]);
+    assert_eq!(server.ips, vec!["127.0.0.1".parse::<IpAddr>().unwrap()]);
+    assert_eq!(server.common_name, Some("localhost".to_string()));
+}
+
+#[test]
+fn test_client_certificate() {
+    let url = start_server(true);
+    let tls = TlsConfig::new().ca_file(cert("ca.pem"));
+    assert!(!get(&url, tls.clone()));
+    assert!(get(&url, tls.client_cert(cert("client.pem"), cert("client.key"))));
+}
+
+#[test]
+fn test_invalid_config() {
+    assert!(TlsConfig::new().pin_sha256("not a fingerprint").context().is_err());
+    assert!(TlsConfig::new().ca_file(cert("missing.pem")).context().is_err());
+    assert!(TlsConfig::new().ca_pem(b"not a certificate").context().is_err());
+
+    // only transports that open their own connections take TLS settings
+    assert!(::mock::MockTransport::new().client().with_tls(TlsConfig::new()).is_err());
+}
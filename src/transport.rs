@@ -0,0 +1,567 @@
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::Duration as StdDuration;
+use chrono::Duration;
+use time::SteadyTime;
+use url::Url;
+use rustc_serialize::json::Json;
+use url::form_urlencoded::serialize_owned;
+use hyper;
+use hyper::error::HttpError;
+use hyper::header::Headers;
+use hyper::method::Method;
+use hyper::method::Method::{Head, Get};
+use hyper::net::{NetworkConnector, NetworkStream};
+use hyper::version::HttpVersion;
+use openssl::ssl::SslStream;
+use error::{Error, EsResult};
+use retry::is_idempotent;
+use tls::{TlsConfig, TlsConnector};
+
+//
+// The HTTP layer underneath `Connection`. Every request goes through a
+// `Transport`, so the HTTP stack can be swapped, wrapped with middleware,
+// or replaced in tests.
+//
+
+// How long to wait to connect, and for each read of the response. The read
+// timeout also bounds each write of the request, so a stalled upload fails
+// too. `None` waits indefinitely.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timeouts {
+    pub connect: Option<Duration>,
+    pub read: Option<Duration>
+}
+
+impl Timeouts {
+    pub fn new() -> Timeouts {
+        Timeouts { connect: None, read: None }
+    }
+
+    pub fn connect(mut self, timeout: Duration) -> Timeouts {
+        self.connect = Some(timeout);
+        self
+    }
+
+    pub fn read(mut self, timeout: Duration) -> Timeouts {
+        self.read = Some(timeout);
+        self
+    }
+
+    // These timeouts, falling back to `defaults` where unset.
+    pub fn or(&self, defaults: &Timeouts) -> Timeouts {
+        Timeouts {
+            connect: self.connect.or(defaults.connect),
+            read: self.read.or(defaults.read)
+        }
+    }
+}
+
+// Per-request settings that are not part of the HTTP request itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestOptions {
+    // overrides of the connection's timeouts
+    pub timeouts: Timeouts,
+    // allow retrying a request whose method isn't idempotent
+    pub retry_non_idempotent: bool,
+    // sent after, and replacing, the connection's default headers
+    pub headers: Vec<(String, String)>
+}
+
+impl RequestOptions {
+    pub fn new() -> RequestOptions {
+        RequestOptions { timeouts: Timeouts::new(), retry_non_idempotent: false, headers: Vec::new() }
+    }
+}
+
+// Sets a header, replacing any of the same name; names are case-insensitive.
+pub fn set_header(headers: &mut Vec<(String, String)>, name: &str, value: &str) {
+    headers.retain(|&(ref n, _)| n.to_lowercase() != name.to_lowercase());
+    headers.push((name.to_string(), value.to_string()));
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransportRequest {
+    pub method: Method,
+    pub path: Vec<String>,
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+    pub timeouts: Timeouts
+}
+
+impl TransportRequest {
+    pub fn new(method: Method, path: Vec<String>) -> TransportRequest {
+        TransportRequest {
+            method: method,
+            path: path,
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            timeouts: Timeouts::new()
+        }
+    }
+
+    // The full url of the request against `host`, whose own path is kept as
+    // a prefix.
+    pub fn url(&self, host: &Url) -> Url {
+        let mut url = host.clone();
+        if let Some(path) = url.path_mut() {
+            // drop the empty segment left by a trailing slash
+            if path.last().map(|s| s.is_empty()).unwrap_or(false) { path.pop(); }
+            path.extend(self.path.iter().cloned());
+        }
+
+        if !self.query.is_empty() {
+            url.query = Some(serialize_owned(&self.query));
+        }
+        url
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransportResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String
+}
+
+impl TransportResponse {
+    // This response, or an error if its status isn't 2xx. Elasticsearch
+    // error bodies become `Error::Elasticsearch`; anything else, such as a
+    // proxy's html page or an empty 404, becomes `Error::Status`.
+    pub fn check(self) -> EsResult<TransportResponse> {
+        if self.status >= 200 && self.status < 300 { return Ok(self); }
+        if let Ok(json) = Json::from_str(&self.body) {
+            if json.find("error").is_some() { return Err(Error::Elasticsearch(json)); }
+        }
+        Err(Error::Status(self.status, self.body))
+    }
+
+    pub fn into_body(self) -> EsResult<String> {
+        self.check().map(|r| r.body)
+    }
+}
+
+// Implementations should honour `request.timeouts` and report an expired
+// timeout as `Error::Timeout`.
+pub trait Transport: Send + Sync {
+    fn perform(&self, host: &Url, request: &TransportRequest) -> EsResult<TransportResponse>;
+
+    // A copy of this transport that uses `tls` for https hosts. Transports
+    // that don't open their own connections can't take TLS settings.
+    fn with_tls_config(&self, _tls: TlsConfig) -> EsResult<Arc<Transport>> {
+        Err(Error::Tls("the transport doesn't take TLS settings".to_string()))
+    }
+}
+
+// Keep-alive settings for `HyperTransport`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolConfig {
+    // idle connections kept per host; 0 disables reuse
+    pub max_idle_per_host: usize,
+    // idle connections older than this are closed instead of reused
+    pub idle_timeout: Option<Duration>
+}
+
+impl PoolConfig {
+    pub fn new() -> PoolConfig {
+        PoolConfig { max_idle_per_host: 8, idle_timeout: Some(Duration::seconds(60)) }
+    }
+
+    pub fn max_idle_per_host(mut self, max: usize) -> PoolConfig {
+        self.max_idle_per_host = max;
+        self
+    }
+
+    pub fn idle_timeout(mut self, timeout: Option<Duration>) -> PoolConfig {
+        self.idle_timeout = timeout;
+        self
+    }
+}
+
+// (host, port, scheme), as hyper connects
+type PoolKey = (String, u16, String);
+
+struct IdleStream {
+    stream: Box<NetworkStream + Send>,
+    since: SteadyTime
+}
+
+struct Pool {
+    config: PoolConfig,
+    idle: Mutex<HashMap<PoolKey, Vec<IdleStream>>>
+}
+
+impl Pool {
+    fn expired(&self, entry: &IdleStream) -> bool {
+        match self.config.idle_timeout {
+            Some(timeout) => SteadyTime::now() - entry.since > timeout,
+            None => false
+        }
+    }
+
+    // the most recently used connection is handed out first
+    fn checkout(&self, key: &PoolKey) -> Option<Box<NetworkStream + Send>> {
+        let mut idle = self.idle.lock().unwrap();
+        if let Some(streams) = idle.get_mut(key) {
+            while let Some(entry) = streams.pop() {
+                if !self.expired(&entry) { return Some(entry.stream); }
+            }
+        }
+        None
+    }
+
+    fn checkin(&self, key: PoolKey, stream: Box<NetworkStream + Send>) {
+        let mut idle = self.idle.lock().unwrap();
+        let streams = idle.entry(key).or_insert_with(Vec::new);
+        streams.retain(|entry| !self.expired(entry));
+        if streams.len() < self.config.max_idle_per_host {
+            streams.push(IdleStream { stream: stream, since: SteadyTime::now() });
+        }
+    }
+}
+
+// What the connector did for one request, so the stream can be returned to
+// the pool under the right key once the response has been read.
+struct Checkout {
+    key: Option<PoolKey>,
+    reused: bool
+}
+
+fn to_std(duration: Duration) -> StdDuration {
+    StdDuration::from_millis(::std::cmp::max(duration.num_milliseconds(), 1) as u64)
+}
+
+// A plain TCP stream whose timeouts can be changed when it is reused.
+// Clones share the socket, so cloning can't fail.
+#[derive(Clone)]
+struct TimedStream(Arc<TcpStream>);
+
+impl TimedStream {
+    fn connect(host: &str, port: u16, timeouts: &Timeouts) -> io::Result<TimedStream> {
+        let stream = match timeouts.connect {
+            None => try!(TcpStream::connect((host, port))),
+            Some(timeout) => {
+                let addrs: Vec<SocketAddr> = try!((host, port).to_socket_addrs()).collect();
+                let mut last_err = io::Error::new(io::ErrorKind::InvalidInput, "host resolved to no addresses");
+                let mut connected = None;
+                for addr in addrs.iter() {
+                    match TcpStream::connect_timeout(addr, to_std(timeout)) {
+                        Ok(stream) => { connected = Some(stream); break; },
+                        Err(e) => last_err = e
+                    }
+                }
+                try!(connected.ok_or(last_err))
+            }
+        };
+        let mut stream = TimedStream(Arc::new(stream));
+        try!(stream.set_timeouts(timeouts));
+        Ok(stream)
+    }
+
+    fn set_timeouts(&mut self, timeouts: &Timeouts) -> io::Result<()> {
+        let read = timeouts.read.map(to_std);
+        try!(self.0.set_read_timeout(read));
+        self.0.set_write_timeout(read)
+    }
+}
+
+impl Read for TimedStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { (&*self.0).read(buf) }
+}
+
+impl Write for TimedStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { (&*self.0).write(buf) }
+    fn flush(&mut self) -> io::Result<()> { (&*self.0).flush() }
+}
+
+impl NetworkStream for TimedStream {
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> { self.0.peer_addr() }
+}
+
+// A TLS stream over a `TimedStream`, so https gets the same timeouts.
+#[derive(Clone)]
+struct TlsStream(SslStream<TimedStream>);
+
+impl TlsStream {
+    fn connect(host: &str, port: u16, timeouts: &Timeouts, tls: &TlsConnector) -> io::Result<TlsStream> {
+        let stream = try!(TimedStream::connect(host, port, timeouts));
+        tls.connect(host, stream).map(TlsStream).map_err(other_io_error)
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.0.read(buf) }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.write(buf) }
+    fn flush(&mut self) -> io::Result<()> { self.0.flush() }
+}
+
+impl NetworkStream for TlsStream {
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> { self.0.get_mut().peer_addr() }
+}
+
+fn other_io_error<E: ToString>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err.to_string())
+}
+
+struct PooledConnector {
+    pool: Arc<Pool>,
+    tls: Arc<TlsConnector>,
+    use_idle: bool,
+    timeouts: Timeouts,
+    checkout: Arc<Mutex<Checkout>>
+}
+
+impl NetworkConnector for PooledConnector {
+    type Stream = Box<NetworkStream + Send>;
+
+    fn connect(&mut self, host: &str, port: u16, scheme: &str) -> io::Result<Box<NetworkStream + Send>> {
+        let key = (host.to_string(), port, scheme.to_string());
+        let mut idle = if self.use_idle { self.pool.checkout(&key) } else { None };
+        if let Some(ref mut stream) = idle {
+            if let Some(timed) = stream.downcast_mut::<TimedStream>() {
+                try!(timed.set_timeouts(&self.timeouts));
+            }
+            if let Some(tls) = stream.downcast_mut::<TlsStream>() {
+                try!(tls.0.get_mut().set_timeouts(&self.timeouts));
+            }
+        }
+
+        {
+            let mut checkout = self.checkout.lock().unwrap();
+            checkout.key = Some(key);
+            checkout.reused = idle.is_some();
+        }
+        match (idle, scheme) {
+            (Some(stream), _) => Ok(stream),
+            (None, "http") => Ok(Box::new(try!(TimedStream::connect(host, port, &self.timeouts)))),
+            (None, "https") => Ok(Box::new(try!(TlsStream::connect(host, port, &self.timeouts, &self.tls)))),
+            (None, _) => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("unsupported scheme: {}", scheme)))
+        }
+    }
+}
+
+// Expired socket timeouts surface as `TimedOut`, or `WouldBlock` on unix.
+fn lift_io_error(err: io::Error) -> Error {
+    match err.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => Error::Timeout(err.to_string()),
+        _ => Error::Io(err)
+    }
+}
+
+fn lift_http_error(err: HttpError) -> Error {
+    match err {
+        HttpError::HttpIoError(e) => lift_io_error(e),
+        other => Error::Http(other)
+    }
+}
+
+// A connection can go back to the pool if the server keeps it open and the
+// response length was known, so its end has been read.
+fn reusable(response: &hyper::client::Response) -> bool {
+    let has = |name: &str, value: &str| response.headers.get_raw(name).map(|values| {
+        values.iter().any(|v| String::from_utf8_lossy(v).to_lowercase().contains(value))
+    }).unwrap_or(false);
+
+    response.version == HttpVersion::Http11
+        && !has("Connection", "close")
+        && (response.headers.get_raw("Content-Length").is_some() || has("Transfer-Encoding", "chunked"))
+}
+
+// The default transport, backed by hyper. Connections are kept alive and
+// reused across requests and threads; clones share the same pool.
+#[derive(Clone)]
+pub struct HyperTransport {
+    pool: Arc<Pool>,
+    tls: Arc<TlsConnector>
+}
+
+impl HyperTransport {
+    pub fn new() -> HyperTransport {
+        HyperTransport::with_pool(PoolConfig::new())
+    }
+
+    pub fn with_pool(config: PoolConfig) -> HyperTransport {
+        HyperTransport {
+            pool: Arc::new(Pool { config: config, idle: Mutex::new(HashMap::new()) }),
+            tls: Arc::new(TlsConnector::new(TlsConfig::new()))
+        }
+    }
+
+    // TLS settings for https hosts. Fails if the certificates or keys can't
+    // be loaded. Connections made with the previous settings aren't reused.
+    pub fn with_tls(mut self, config: TlsConfig) -> EsResult<HyperTransport> {
+        self.tls = Arc::new(try!(TlsConnector::build(config)));
+        self.pool = Arc::new(Pool { config: self.pool.config.clone(), idle: Mutex::new(HashMap::new()) });
+        Ok(self)
+    }
+
+    fn send(&self, host: &Url, request: &TransportRequest, use_idle: bool,
+            checkout: Arc<Mutex<Checkout>>) -> EsResult<TransportResponse> {
+        let mut headers = Headers::new();
+        for &(ref name, ref value) in request.headers.iter() {
+            headers.set_raw(name.to_string(), vec![value.as_bytes().to_vec()]);
+        }
+
+        // existence checks are sent as GETs; only the status matters
+        let method = match request.method {
+            Head => Get,
+            ref other => other.clone()
+        };
+
+        let mut hyper_client = hyper::Client::with_connector(PooledConnector {
+            pool: self.pool.clone(),
+            tls: self.tls.clone(),
+            use_idle: use_idle,
+            timeouts: request.timeouts,
+            checkout: checkout.clone()
+        });
+        let builder = hyper_client.request(method, request.url(host)).headers(headers);
+        let mut response = try!(match request.body {
+            Some(ref bod) if request.method != Get && request.method != Head => builder.body(&bod[..]).send(),
+            _ => builder.send()
+        }.map_err(lift_http_error));
+
+        let mut body = String::new();
+        try!(response.read_to_string(&mut body).map_err(lift_io_error));
+        let result = TransportResponse {
+            status: response.status.to_u16(),
+            headers: response.headers.iter().map(|h| (h.name().to_string(), h.value_string())).collect(),
+            body: body
+        };
+
+        if reusable(&response) {
+            if let Some(key) = checkout.lock().unwrap().key.take() {
+                self.pool.checkin(key, response.into_inner());
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Transport for HyperTransport {
+    fn perform(&self, host: &Url, request: &TransportRequest) -> EsResult<TransportResponse> {
+        let checkout = Arc::new(Mutex::new(Checkout { key: None, reused: false }));
+        let result = self.send(host, request, true, checkout.clone());
+        let reused = checkout.lock().unwrap().reused;
+        match result {
+            // the server may have closed a pooled connection while it was
+            // idle, so try once more on a new one. Other requests may already
+            // have been applied, so they are left to the retry policy.
+            Err(ref e) if reused && !e.is_timeout() && is_idempotent(&request.method) => {
+                self.send(host, request, false, checkout)
+            },
+            result => result
+        }
+    }
+
+    fn with_tls_config(&self, tls: TlsConfig) -> EsResult<Arc<Transport>> {
+        Ok(Arc::new(try!(self.clone().with_tls(tls))))
+    }
+}
+
+#[test]
+fn test_request_url() {
+    let mut request = TransportRequest::new(Method::Get, vec!["logs".to_string(), "_search".to_string()]);
+    request.query.push(("q".to_string(), "user:kimchy".to_string()));
+
+    let host = Url::parse("http://localhost:9200/").unwrap();
+    assert_eq!(request.url(&host).serialize(), "http://localhost:9200/logs/_search?q=user%3Akimchy");
+
+    let proxied = Url::parse("http://proxy/es").unwrap();
+    assert_eq!(request.url(&proxied).serialize(), "http://proxy/es/logs/_search?q=user%3Akimchy");
+}
+
+#[test]
+fn test_read_timeout() {
+    use std::net::TcpListener;
+    use connection::Connection;
+
+    // accepted by the OS but never answered
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+
+    let conn = Connection::new(host).with_timeouts(Timeouts::new().read(Duration::milliseconds(50)));
+    let err = conn.request(Get, Vec::new(), Vec::new(), None, &RequestOptions::new()).unwrap_err();
+    assert!(err.is_timeout());
+}
+
+// Answers every request with `{}`, counting the connections it accepts.
+// With `close`, each connection is closed after one response without
+// telling the client, as an idle timeout on the server would.
+#[cfg(test)]
+fn start_keep_alive_server(close: bool) -> (Url, Arc<::std::sync::atomic::AtomicUsize>) {
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let counter = accepted.clone();
+    thread::spawn(move || {
+        for tcp in listener.incoming() {
+            let mut stream = tcp.unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+            thread::spawn(move || {
+                let mut pending = Vec::new();
+                let mut buf = [0; 1024];
+                loop {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => pending.extend(buf[..n].iter().cloned())
+                    }
+                    while let Some(end) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+                        pending.drain(..end + 4);
+                        if stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}").is_err() || close {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    (host, accepted)
+}
+
+#[test]
+fn test_connection_reuse() {
+    use std::sync::atomic::Ordering;
+
+    let (host, accepted) = start_keep_alive_server(false);
+    let transport = HyperTransport::new();
+    for _ in 0..3 {
+        let response = transport.perform(&host, &TransportRequest::new(Get, Vec::new())).unwrap();
+        assert_eq!(response.body, "{}");
+    }
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_stale_connection_replay() {
+    use std::sync::atomic::Ordering;
+    use std::thread;
+
+    let (host, accepted) = start_keep_alive_server(true);
+    let transport = HyperTransport::new();
+    let pooled = |method: Method| {
+        transport.perform(&host, &TransportRequest::new(Get, Vec::new())).unwrap();
+        // let the server's close reach the pooled socket
+        thread::sleep(StdDuration::from_millis(50));
+        transport.perform(&host, &TransportRequest::new(method, Vec::new()))
+    };
+
+    // a GET is sent again on a new connection
+    assert!(pooled(Get).is_ok());
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+
+    // a POST may have been applied, so it fails instead
+    assert!(pooled(Method::Post).is_err());
+    assert_eq!(accepted.load(Ordering::SeqCst), 3);
+}
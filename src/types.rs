@@ -3,7 +3,7 @@ use std::str::FromStr;
 use std::fmt;
 use std::string::ToString;
 use chrono::{Duration, DateTime, UTC};
-use rustc_serialize::json;
+use rustc_serialize::{json, Decodable, Decoder};
 
 pub trait QueryParam {
     fn get_name(&self) -> &'static str;
@@ -38,62 +38,139 @@ impl From<&'static str> for Parent {
     }
 }
 
+impl_as_ref!{ pub struct ActiveOnly(bool) }
 impl_as_ref!{ pub struct AllowNoIndices(bool) }
 impl_as_ref!{ pub struct Analyzer(String) }
+impl_as_ref!{ pub struct Columns(StringList) }
+impl_as_ref!{ pub struct Detailed(bool) }
+impl_as_ref!{ pub struct DryRun(bool) }
+impl_as_ref!{ pub struct Explain(bool) }
+impl_as_ref!{ pub struct Fielddata(bool) }
 impl_as_ref!{ pub struct Fields(StringList) }
+impl_as_ref!{ pub struct FlatSettings(bool) }
+impl_as_ref!{ pub struct Flush(bool) }
+impl_as_ref!{ pub struct Force(bool) }
+impl_as_ref!{ pub struct Format(String) }
+impl_as_ref!{ pub struct IgnoreIdleThreads(bool) }
 impl_as_ref!{ pub struct IgnoreUnavailable(bool) }
+impl_as_ref!{ pub struct IncludeDefaults(bool) }
+impl_as_ref!{ pub struct IncludeDiskInfo(bool) }
+impl_as_ref!{ pub struct IncludeYesDecisions(bool) }
 impl_as_ref!{ pub struct Index(String) }
+impl_as_ref!{ pub struct Interval(Timeout) }
 impl_as_ref!{ pub struct Lang(String) }
 impl_as_ref!{ pub struct Local(bool) }
 impl_as_ref!{ pub struct MasterTimeout(Timeout) }
+impl_as_ref!{ pub struct MaxNumSegments(u64) }
 impl_as_ref!{ pub struct MinScore(f64) }
+impl_as_ref!{ pub struct OnlyExpungeDeletes(bool) }
+impl_as_ref!{ pub struct Pipeline(String) }
 impl_as_ref!{ pub struct Preference(String) }
+impl_as_ref!{ pub struct PreserveExisting(bool) }
+impl_as_ref!{ pub struct QueryCache(bool) }
 impl_as_ref!{ pub struct Realtime(bool) }
 impl_as_ref!{ pub struct Refresh(bool) }
+impl_as_ref!{ pub struct RequestCache(bool) }
+impl_as_ref!{ pub struct RetryFailed(bool) }
 impl_as_ref!{ pub struct RetryOnConflict(usize) }
 impl_as_ref!{ pub struct Routing(String) }
 impl_as_ref!{ pub struct Script(String) }
 impl_as_ref!{ pub struct ScriptId(String) }
 impl_as_ref!{ pub struct ScriptedUpsert(bool) }
+impl_as_ref!{ pub struct ShowHeaders(bool) }
+impl_as_ref!{ pub struct Snapshots(u64) }
+impl_as_ref!{ pub struct SortBy(StringList) }
 impl_as_ref!{ pub struct Source(String) }
 impl_as_ref!{ pub struct SourceExclude(StringList) }
 impl_as_ref!{ pub struct SourceInclude(StringList) }
+impl_as_ref!{ pub struct Threads(u64) }
 impl_as_ref!{ pub struct Timestamp(DateTime<UTC>) }
 impl_as_ref!{ pub struct Ttl(Duration) }
 impl_as_ref!{ pub struct Type(String) }
+impl_as_ref!{ pub struct Verbose(bool) }
+impl_as_ref!{ pub struct Verify(bool) }
 impl_as_ref!{ pub struct Version(i64) }
+impl_as_ref!{ pub struct WaitForActiveShards(String) }
+impl_as_ref!{ pub struct WaitForCompletion(bool) }
+impl_as_ref!{ pub struct WaitForNoRelocatingShards(bool) }
+impl_as_ref!{ pub struct WaitForNodes(String) }
+impl_as_ref!{ pub struct WaitForStatus(HealthStatus) }
+impl_as_ref!{ pub struct WaitIfOngoing(bool) }
 impl_as_ref!{ pub struct _Source(bool) }
 
+impl From<&'static str> for Lang {
+    fn from(a: &'static str) -> Self {
+        Lang(a.to_string())
+    }
+}
+
+impl_query_param!(ActiveOnly, "active_only", { |x| x.0.to_string() });
 impl_query_param!(AllowNoIndices, "allow_no_indices", { |x| x.0.to_string() });
 impl_query_param!(Analyzer, "analyzer", { |x| x.0.to_string() });
+impl_query_param!(ByteUnit, "bytes", { |x| x.to_string() });
+impl_query_param!(Columns, "h", { |x| x.0.to_string() });
 impl_query_param!(Consistency, "consistency", { |x| x.to_string() });
+impl_query_param!(Detailed, "detailed", { |x| x.0.to_string() });
+impl_query_param!(DryRun, "dry_run", { |x| x.0.to_string() });
 impl_query_param!(ExpandWildcards, "expand_wildcards", { |x| x.to_string() });
+impl_query_param!(Explain, "explain", { |x| x.0.to_string() });
+impl_query_param!(Fielddata, "fielddata", { |x| x.0.to_string() });
 impl_query_param!(Fields, "fields", { |x| x.0.to_string() });
+impl_query_param!(FlatSettings, "flat_settings", { |x| x.0.to_string() });
+impl_query_param!(Flush, "flush", { |x| x.0.to_string() });
+impl_query_param!(Force, "force", { |x| x.0.to_string() });
+impl_query_param!(Format, "format", { |x| x.0.to_string() });
+impl_query_param!(HotThreadsType, "type", { |x| x.to_string() });
+impl_query_param!(IgnoreIdleThreads, "ignore_idle_threads", { |x| x.0.to_string() });
 impl_query_param!(IgnoreUnavailable, "ignore_unavailable", { |x| x.0.to_string() });
+impl_query_param!(IncludeDefaults, "include_defaults", { |x| x.0.to_string() });
+impl_query_param!(IncludeDiskInfo, "include_disk_info", { |x| x.0.to_string() });
+impl_query_param!(IncludeYesDecisions, "include_yes_decisions", { |x| x.0.to_string() });
 impl_query_param!(Index, "index", { |x| x.0.to_string() });
+impl_query_param!(Interval, "interval", { |x| x.0.to_time_value() });
 impl_query_param!(Lang, "lang", { |x| x.0.to_string() });
+impl_query_param!(Level, "level", { |x| x.to_string() });
 impl_query_param!(Local, "local", { |x| x.0.to_string() });
 impl_query_param!(MasterTimeout, "master_timeout", { |x| x.0.to_string() });
+impl_query_param!(MaxNumSegments, "max_num_segments", { |x| x.0.to_string() });
 impl_query_param!(MinScore, "min_score", { |x| x.0.to_string() });
+impl_query_param!(OnlyExpungeDeletes, "only_expunge_deletes", { |x| x.0.to_string() });
 impl_query_param!(OpType, "op_type", { |x| x.to_string() });
 impl_query_param!(Parent, "parent", { |x| x.0.to_string() });
+impl_query_param!(Pipeline, "pipeline", { |x| x.0.to_string() });
 impl_query_param!(Preference, "preference", { |x| x.0.to_string() });
+impl_query_param!(PreserveExisting, "preserve_existing", { |x| x.0.to_string() });
+impl_query_param!(QueryCache, "query", { |x| x.0.to_string() });
 impl_query_param!(Realtime, "realtime", { |x| x.0.to_string() });
 impl_query_param!(Refresh, "refresh", { |x| x.0.to_string() });
+impl_query_param!(RequestCache, "request", { |x| x.0.to_string() });
+impl_query_param!(RetryFailed, "retry_failed", { |x| x.0.to_string() });
 impl_query_param!(RetryOnConflict, "retry_on_conflict", { |x| x.0.to_string() });
 impl_query_param!(Routing, "routing", { |x| x.0.to_string() });
 impl_query_param!(Script, "script", { |x| x.0.to_string() });
 impl_query_param!(ScriptId, "script_id", { |x| x.0.to_string() });
 impl_query_param!(ScriptedUpsert, "scripted_upsert", { |x| x.0.to_string() });
+impl_query_param!(ShowHeaders, "v", { |x| x.0.to_string() });
+impl_query_param!(Snapshots, "snapshots", { |x| x.0.to_string() });
+impl_query_param!(SortBy, "s", { |x| x.0.to_string() });
 impl_query_param!(Source, "source", { |x| x.0.to_string() });
 impl_query_param!(SourceExclude, "_source_exclude", { |x| x.0.to_string() });
 impl_query_param!(SourceInclude, "_source_include", { |x| x.0.to_string() });
+impl_query_param!(Threads, "threads", { |x| x.0.to_string() });
 impl_query_param!(Timeout, "timeout", { |x| x.to_string() });
 impl_query_param!(Timestamp, "timestamp", { |x| x.0.to_string() });
 impl_query_param!(Ttl, "ttl", { |x| x.0.num_milliseconds().to_string() });
 impl_query_param!(Type, "type", { |x| x.0.to_string() });
+impl_query_param!(Verbose, "verbose", { |x| x.0.to_string() });
+impl_query_param!(Verify, "verify", { |x| x.0.to_string() });
 impl_query_param!(Version, "version", { |x| x.0.to_string() });
 impl_query_param!(VersionType, "version_type", { |x| x.to_string() });
+impl_query_param!(WaitForActiveShards, "wait_for_active_shards", { |x| x.0.to_string() });
+impl_query_param!(WaitForCompletion, "wait_for_completion", { |x| x.0.to_string() });
+impl_query_param!(WaitForNoRelocatingShards, "wait_for_no_relocating_shards", { |x| x.0.to_string() });
+impl_query_param!(WaitForNodes, "wait_for_nodes", { |x| x.0.to_string() });
+impl_query_param!(WaitForStatus, "wait_for_status", { |x| x.0.to_string() });
+impl_query_param!(WaitIfOngoing, "wait_if_ongoing", { |x| x.0.to_string() });
 impl_query_param!(_Source, "_source", { |x| x.0.to_string() });
 
 #[derive(Debug, Clone, PartialEq)]
@@ -116,6 +193,97 @@ impl ToString for ExpandWildcards {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub enum Level {
+    Cluster,
+    Node,
+    Indices,
+    Shards
+}
+
+impl ToString for Level {
+    fn to_string(&self) -> String {
+        match *self {
+            Level::Cluster => "cluster".to_string(),
+            Level::Node => "node".to_string(),
+            Level::Indices => "indices".to_string(),
+            Level::Shards => "shards".to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub enum ByteUnit {
+    B,
+    Kb,
+    Mb,
+    Gb,
+    Tb,
+    Pb
+}
+
+impl ToString for ByteUnit {
+    fn to_string(&self) -> String {
+        match *self {
+            ByteUnit::B => "b".to_string(),
+            ByteUnit::Kb => "kb".to_string(),
+            ByteUnit::Mb => "mb".to_string(),
+            ByteUnit::Gb => "gb".to_string(),
+            ByteUnit::Tb => "tb".to_string(),
+            ByteUnit::Pb => "pb".to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(RustcDecodable, RustcEncodable)]
+pub enum HotThreadsType {
+    Cpu,
+    Wait,
+    Block
+}
+
+impl ToString for HotThreadsType {
+    fn to_string(&self) -> String {
+        match *self {
+            HotThreadsType::Cpu => "cpu".to_string(),
+            HotThreadsType::Wait => "wait".to_string(),
+            HotThreadsType::Block => "block".to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthStatus {
+    Green,
+    Yellow,
+    Red
+}
+
+impl ToString for HealthStatus {
+    fn to_string(&self) -> String {
+        match *self {
+            HealthStatus::Green => "green".to_string(),
+            HealthStatus::Yellow => "yellow".to_string(),
+            HealthStatus::Red => "red".to_string()
+        }
+    }
+}
+
+// the cluster reports status in lower case, unlike the derived decoding
+impl Decodable for HealthStatus {
+    fn decode<D: Decoder>(d: &mut D) -> Result<HealthStatus, D::Error> {
+        match try!(d.read_str()).as_str() {
+            "green" => Ok(HealthStatus::Green),
+            "yellow" => Ok(HealthStatus::Yellow),
+            "red" => Ok(HealthStatus::Red),
+            other => Err(d.error(&format!("unknown health status `{}`", other)))
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[derive(RustcDecodable, RustcEncodable)]
 pub enum Consistency {
@@ -199,6 +367,14 @@ impl Timeout {
     }
 }
 
+impl Timeout {
+    // `to_string` yields bare milliseconds for query params; request bodies
+    // such as rollover conditions need an explicit unit
+    pub fn to_time_value(&self) -> String {
+        format!("{}ms", self.0.num_milliseconds())
+    }
+}
+
 impl ToString for Timeout {
     fn to_string(&self) -> String {
         let Timeout(duration) = *self;